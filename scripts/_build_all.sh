@@ -42,6 +42,8 @@ TANZIL_SIMPLE_OUTPUT="$MYDIR/../rasm_arch_data/mushaf_simple.json"
 TANZIL_UTHMANI_OUTPUT="$MYDIR/../rasm_arch_data/mushaf_uthmani.json"
 DECOTYPE_OUTPUT="$MYDIR/../rasm_arch_data/mushaf_dt.json"
 
+RASM="cargo run --quiet --release --manifest-path $MYDIR/../src/rust/rasm/Cargo.toml --"
+
 HELP='\n'\
 'usage:\n'\
 "\tbash $0 [options]\n"\
@@ -71,18 +73,18 @@ parse_arguments()
 parse_arguments $@
 
 if [[ ! -f $TANZIL_SIMPLE_OUTPUT || $FORCE_FLAG -eq 1 ]] ; then
-    cat $TANZIL_SIMPLE_INPUT | python "$MYDIR"/_build_qstruct.py > $TANZIL_SIMPLE_OUTPUT &&
+    $RASM build $TANZIL_SIMPLE_INPUT $TANZIL_SIMPLE_OUTPUT &&
     echo "tanzil simple processed!" >/dev/stderr
 fi &&
 
 if [[ ! -f $TANZIL_UTHMANI_OUTPUT || $FORCE_FLAG -eq 1 ]] ; then
-    cat $TANZIL_UTHMANI_INPUT | python "$MYDIR"/_build_qstruct.py > $TANZIL_UTHMANI_OUTPUT &&
+    $RASM build $TANZIL_UTHMANI_INPUT $TANZIL_UTHMANI_OUTPUT &&
     echo "tanzil uthmani processed!" >/dev/stderr
 fi  &&
 
 if [[ ! -f $DECOTYPE_OUTPUT || $FORCE_FLAG -eq 1 ]] ; then
     if [ -f $DECOTYPE_INPUT ] ; then
-        cat $DECOTYPE_INPUT | python "$MYDIR"/_build_qstruct_dt.py > $DECOTYPE_OUTPUT &&
+        $RASM build --format decotype $DECOTYPE_INPUT $DECOTYPE_OUTPUT &&
         echo "decotype processed!" >/dev/stderr
     fi
 fi
@@ -0,0 +1,347 @@
+/*
+ *    align.rs
+ *
+ * alignment of transcriptions with the Quran at the letterblock level
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+
+/// Letterblock of the Quran with its archigraphemes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuranBlock {
+    /// (sura, vers, word, block), starting in 1
+    pub index: [usize; 4],
+    /// original text of the block
+    pub tok: String,
+    /// archigraphemes in Latin script
+    pub lat: String,
+    /// paleo-orthographic representation
+    pub pal: String,
+}
+
+/// Operation in the alignment of the transcription (ms) with the Quran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// same block in both
+    Match,
+    /// different blocks
+    Sub,
+    /// block of the transcription missing in the Quran
+    Ins,
+    /// block of the Quran missing in the transcription
+    Del,
+}
+
+impl Edit {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edit::Match => "match",
+            Edit::Sub => "sub",
+            Edit::Ins => "ins",
+            Edit::Del => "del",
+        }
+    }
+}
+
+/// Step of an alignment, with the positions involved in the quran blocks and in the transcription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub edit: Edit,
+    pub quran: Option<usize>,
+    pub ms: Option<usize>,
+}
+
+/// Best local match of a transcription inside the Quran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    /// first and last matched positions in the quran blocks
+    pub start: usize,
+    pub end: usize,
+    /// sum of the costs of all edits
+    pub distance: f64,
+    pub steps: Vec<Step>,
+}
+
+/// All blocks of the mushaf that contain archigraphemes, in order.
+pub fn quran_blocks(conv: &Converter, quran: &Mushaf) -> Vec<QuranBlock> {
+    quran.blocks()
+        .map(|(index, (tok, pal))| QuranBlock { index, tok: tok.clone(), lat: conv.strip_paleo(pal).into_owned(), pal: pal.clone() })
+        .filter(|block| !block.lat.is_empty())
+        .collect()
+}
+
+/// Levenshtein distance between the archigraphemes of two blocks, divided by the length of the longest,
+/// so that substituting a block costs between 0 and 1.
+pub fn block_distance(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut diag = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i-1] == b[j-1] { diag } else { diag + 1 };
+            diag = row[j];
+            row[j] = cost.min(row[j] + 1).min(row[j-1] + 1);
+        }
+    }
+    row[b.len()] as f64 / a.len().max(b.len()) as f64
+}
+
+/// Substitution costs between every distinct block of the transcription and of the Quran.
+struct Costs {
+    quran_ids: Vec<usize>,
+    ms_ids: Vec<usize>,
+    table: Vec<Vec<f64>>,
+}
+
+impl Costs {
+
+    fn new(quran: &[QuranBlock], ms: &[String]) -> Costs {
+        let mut quran_uniq: HashMap<&str, usize> = HashMap::new();
+        let quran_ids = quran.iter().map(|b| {
+            let n = quran_uniq.len();
+            *quran_uniq.entry(b.lat.as_str()).or_insert(n)
+        }).collect();
+        let mut ms_uniq: HashMap<&str, usize> = HashMap::new();
+        let ms_ids = ms.iter().map(|b| {
+            let n = ms_uniq.len();
+            *ms_uniq.entry(b.as_str()).or_insert(n)
+        }).collect();
+
+        let mut quran_lat = vec![""; quran_uniq.len()];
+        quran_uniq.into_iter().for_each(|(lat, i)| quran_lat[i] = lat);
+        let mut table = vec![Vec::new(); ms_uniq.len()];
+        for (lat, i) in ms_uniq {
+            table[i] = quran_lat.iter().map(|q| block_distance(lat, q)).collect();
+        }
+
+        Costs { quran_ids, ms_ids, table }
+    }
+
+    fn sub(&self, i: usize, j: usize) -> f64 {
+        self.table[self.ms_ids[i]][self.quran_ids[j]]
+    }
+}
+
+/// Semi-global edit distance: the whole transcription against any stretch of quran[offset..offset+len].
+/// Return the matrix, with the transcription in rows.
+fn matrix(costs: &Costs, m: usize, offset: usize, len: usize) -> Vec<Vec<f64>> {
+    let mut d = vec![vec![0.0; len+1]; m+1];
+    for i in 1..=m {
+        d[i][0] = i as f64;
+        for j in 1..=len {
+            let sub = d[i-1][j-1] + costs.sub(i-1, offset+j-1);
+            d[i][j] = sub.min(d[i-1][j] + 1.0).min(d[i][j-1] + 1.0);
+        }
+    }
+    d
+}
+
+/// Find the stretch of the Quran closest to the transcription, given as Latin blocks, and align them.
+pub fn align(quran: &[QuranBlock], ms: &[String]) -> Option<Alignment> {
+
+    if ms.is_empty() || quran.is_empty() {
+        return None;
+    }
+
+    let costs = Costs::new(quran, ms);
+    let (m, n) = (ms.len(), quran.len());
+
+    // first pass, keeping only two rows, to find where the best match ends
+    let mut prev = vec![0.0; n+1];
+    let mut cur = vec![0.0; n+1];
+    for i in 1..=m {
+        cur[0] = i as f64;
+        for j in 1..=n {
+            let sub = prev[j-1] + costs.sub(i-1, j-1);
+            cur[j] = sub.min(prev[j] + 1.0).min(cur[j-1] + 1.0);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    let end = (1..=n).min_by(|&a, &b| prev[a].total_cmp(&prev[b])).unwrap();
+
+    // second pass, with traceback, over a window that surely contains the whole match
+    let offset = end.saturating_sub(2 * m + 1);
+    let len = end - offset;
+    let d = matrix(&costs, m, offset, len);
+
+    let mut steps = Vec::new();
+    let (mut i, mut j) = (m, len);
+    while i > 0 {
+        let (q, s) = (offset + j, i);
+        if j > 0 && d[i][j] == d[i-1][j-1] + costs.sub(i-1, q-1) {
+            let edit = if quran[q-1].lat == ms[s-1] { Edit::Match } else { Edit::Sub };
+            steps.push(Step { edit, quran: Some(q-1), ms: Some(s-1) });
+            i -= 1;
+            j -= 1;
+        } else if d[i][j] == d[i-1][j] + 1.0 {
+            steps.push(Step { edit: Edit::Ins, quran: None, ms: Some(s-1) });
+            i -= 1;
+        } else {
+            steps.push(Step { edit: Edit::Del, quran: Some(q-1), ms: None });
+            j -= 1;
+        }
+    }
+    steps.reverse();
+
+    let start = steps.iter().find_map(|s| s.quran)?;
+    let end = steps.iter().rev().find_map(|s| s.quran)?;
+
+    Some(Alignment { start, end, distance: d[m][len], steps })
+}
+
+/// Largest number of cells of the cost matrix for which the global alignment keeps the whole traceback.
+const DIRECT_CELLS: usize = 1 << 20;
+
+/// Global alignment of two sequences of lengths n and m, with unit cost for insertions and deletions
+/// and sub(i, j) for aligning a[i] with b[j]. Return the pairs of aligned positions in order; None
+/// marks a gap. Long sequences are split with Hirschberg's method, so that memory stays linear.
+pub fn global<F: Fn(usize, usize) -> f64>(n: usize, m: usize, sub: F) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut pairs = Vec::with_capacity(n.max(m));
+    hirschberg(&sub, (0, n), (0, m), DIRECT_CELLS, &mut pairs);
+    pairs
+}
+
+/// Global alignment of two sequences of Latin rasm, words or blocks, by the distance of their archigraphemes.
+/// Replacing an item costs twice its block distance, so that it costs as much as omitting it and adding
+/// another one, and an omission next to an addition is not reported as two substitutions.
+pub fn global_rasm<S: AsRef<str>>(a: &[S], b: &[S]) -> Vec<(Option<usize>, Option<usize>)> {
+    global(a.len(), b.len(), |i, j| 2.0 * block_distance(a[i].as_ref(), b[j].as_ref()))
+}
+
+/// Align a[a0..a1] with b[b0..b1], splitting a in half until the cost matrix fits within limit cells.
+fn hirschberg<F: Fn(usize, usize) -> f64>(sub: &F, (a0, a1): (usize, usize), (b0, b1): (usize, usize), limit: usize,
+                                          pairs: &mut Vec<(Option<usize>, Option<usize>)>) {
+
+    if a1 - a0 <= 1 || (a1 - a0 + 1) * (b1 - b0 + 1) <= limit {
+        direct(sub, (a0, a1), (b0, b1), pairs);
+        return;
+    }
+
+    // the best path crosses the middle row of a where the costs of both halves add up to the least
+    let mid = (a0 + a1) / 2;
+    let fwd = last_row(|i, j| sub(a0 + i, b0 + j), mid - a0, b1 - b0);
+    let bwd = last_row(|i, j| sub(a1 - 1 - i, b1 - 1 - j), a1 - mid, b1 - b0);
+    let split = (0..=b1-b0).min_by(|&x, &y| (fwd[x] + bwd[b1-b0-x]).total_cmp(&(fwd[y] + bwd[b1-b0-y]))).unwrap();
+
+    hirschberg(sub, (a0, mid), (b0, b0 + split), limit, pairs);
+    hirschberg(sub, (mid, a1), (b0 + split, b1), limit, pairs);
+}
+
+/// Last row of the cost matrix of the global alignment of sequences of lengths n and m.
+fn last_row<F: Fn(usize, usize) -> f64>(sub: F, n: usize, m: usize) -> Vec<f64> {
+    let mut prev: Vec<f64> = (0..=m).map(|j| j as f64).collect();
+    let mut cur = vec![0.0; m+1];
+    for i in 1..=n {
+        cur[0] = i as f64;
+        for j in 1..=m {
+            cur[j] = (prev[j-1] + sub(i-1, j-1)).min(prev[j] + 1.0).min(cur[j-1] + 1.0);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev
+}
+
+/// Align a[a0..a1] with b[b0..b1] keeping the whole traceback. Ties prefer the diagonal.
+fn direct<F: Fn(usize, usize) -> f64>(sub: &F, (a0, a1): (usize, usize), (b0, b1): (usize, usize),
+                                      pairs: &mut Vec<(Option<usize>, Option<usize>)>) {
+
+    let (n, m) = (a1 - a0, b1 - b0);
+
+    // traceback: 0 diagonal, 1 gap in b, 2 gap in a
+    let mut back = vec![vec![0u8; m+1]; n+1];
+    let mut prev: Vec<f64> = (0..=m).map(|j| j as f64).collect();
+    let mut cur = vec![0.0; m+1];
+    back[0].iter_mut().skip(1).for_each(|t| *t = 2);
+
+    for (i, row) in back.iter_mut().enumerate().skip(1) {
+        cur[0] = i as f64;
+        row[0] = 1;
+        for j in 1..=m {
+            let diag = prev[j-1] + sub(a0 + i-1, b0 + j-1);
+            let up = prev[j] + 1.0;
+            let left = cur[j-1] + 1.0;
+            (cur[j], row[j]) = if diag <= up && diag <= left { (diag, 0) } else if up <= left { (up, 1) } else { (left, 2) };
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    let start = pairs.len();
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        match back[i][j] {
+            0 => { pairs.push((Some(a0 + i-1), Some(b0 + j-1))); i -= 1; j -= 1 }
+            1 => { pairs.push((Some(a0 + i-1), None)); i -= 1 }
+            _ => { pairs.push((None, Some(b0 + j-1))); j -= 1 }
+        }
+    }
+    pairs[start..].reverse();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn cost(pairs: &[(Option<usize>, Option<usize>)], sub: impl Fn(usize, usize) -> f64) -> f64 {
+        pairs.iter().map(|&p| match p { (Some(i), Some(j)) => sub(i, j), _ => 1.0 }).sum()
+    }
+
+    #[test]
+    fn split_alignment_is_optimal() {
+        // two pseudo-random sequences over a small alphabet
+        let seq = |seed: u64, len: usize| -> Vec<u8> {
+            let mut x = seed;
+            (0..len).map(|_| { x = x.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407); (x >> 60) as u8 % 5 }).collect()
+        };
+        let (a, b) = (seq(1, 301), seq(2, 257));
+        let sub = |i: usize, j: usize| if a[i] == b[j] { 0.0 } else { 2.0 * (a[i] as f64 - b[j] as f64).abs() / 4.0 };
+
+        let mut whole = Vec::new();
+        direct(&sub, (0, a.len()), (0, b.len()), &mut whole);
+        for limit in [4, 100, 5000] {
+            let mut split = Vec::new();
+            hirschberg(&sub, (0, a.len()), (0, b.len()), limit, &mut split);
+            assert_eq!(split.iter().filter_map(|p| p.0).collect::<Vec<_>>(), (0..a.len()).collect::<Vec<_>>());
+            assert_eq!(split.iter().filter_map(|p| p.1).collect::<Vec<_>>(), (0..b.len()).collect::<Vec<_>>());
+            assert!((cost(&split, sub) - cost(&whole, sub)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn empty_sequences() {
+        assert_eq!(global(0, 0, |_, _| 0.0), Vec::new());
+        assert_eq!(global(2, 0, |_, _| 0.0), vec![(Some(0), None), (Some(1), None)]);
+        assert_eq!(global(0, 1, |_, _| 0.0), vec![(None, Some(0))]);
+    }
+}
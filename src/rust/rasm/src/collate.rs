@@ -0,0 +1,136 @@
+/*
+ *    collate.rs
+ *
+ * collation of two Arabic texts by rasm, word by word and block by block
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use crate::align::global_rasm;
+use crate::convert::Converter;
+use crate::unpaleo::nfd;
+
+/// Token of a witness with its archigraphemes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// original token, with its dots and vowels
+    pub ori: String,
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub lat: String,
+}
+
+/// Level at which two readings differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// different rasm, or word present only in one witness
+    Skeleton,
+    /// same rasm, different dotted consonants
+    Dotting,
+    /// same dotted consonants, different vowels or other diacritics
+    Vocalisation,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Skeleton => "skeleton",
+            Kind::Dotting => "dotting",
+            Kind::Vocalisation => "vocalisation",
+        }
+    }
+}
+
+/// Difference between the witnesses at one aligned pair of words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub kind: Kind,
+    /// position of the word in each witness, None if it is missing there
+    pub a: Option<usize>,
+    pub b: Option<usize>,
+    /// differing blocks, as aligned pairs; empty unless kind is Skeleton
+    pub blocks: Vec<(Option<String>, Option<String>)>,
+}
+
+/// Split text into Arabic tokens and reduce them into archigraphemes, keeping the original forms.
+pub fn tokens(conv: &Converter, text: &str) -> Vec<Token> {
+    let text = conv.normalise(text);
+    let text = conv.re_ara.replace_all(&text, " ");
+    text.split_whitespace()
+        .map(|tok| Token { ori: tok.to_string(), lat: conv.reduce(tok).lat })
+        .filter(|tok| !tok.lat.is_empty())
+        .collect()
+}
+
+/// Align the witnesses word by word by their rasm and report every difference in order.
+pub fn collate(conv: &Converter, a: &[Token], b: &[Token]) -> Vec<Difference> {
+
+    let rasm = |tok: &Token| tok.lat.split_whitespace().collect::<String>();
+    // dotted letters without marks, with hamza and madda decomposed from their seats so that أكل and اكل
+    // only differ in vocalisation
+    let letters = |ori: &str| conv.re_clean.replace_all(&nfd(ori), "").into_owned();
+    let (rasm_a, rasm_b): (Vec<String>, Vec<String>) = (a.iter().map(rasm).collect(), b.iter().map(rasm).collect());
+
+    let mut diffs = Vec::new();
+
+    for (i, j) in global_rasm(&rasm_a, &rasm_b) {
+
+        let (ta, tb) = match (i, j) {
+            (Some(i), Some(j)) => (&a[i], &b[j]),
+            _ => {
+                let blocks = match (i, j) {
+                    (Some(i), _) => a[i].lat.split_whitespace().map(|bl| (Some(bl.to_string()), None)).collect(),
+                    (_, Some(j)) => b[j].lat.split_whitespace().map(|bl| (None, Some(bl.to_string()))).collect(),
+                    _ => Vec::new(),
+                };
+                diffs.push(Difference { kind: Kind::Skeleton, a: i, b: j, blocks });
+                continue;
+            }
+        };
+
+        let kind = if ta.lat != tb.lat {
+            Kind::Skeleton
+        } else if ta.ori == tb.ori {
+            continue;
+        } else if letters(&ta.ori) != letters(&tb.ori) {
+            Kind::Dotting
+        } else {
+            Kind::Vocalisation
+        };
+
+        let blocks = if kind == Kind::Skeleton {
+            let (ba, bb): (Vec<&str>, Vec<&str>) = (ta.lat.split_whitespace().collect(), tb.lat.split_whitespace().collect());
+            global_rasm(&ba, &bb)
+                .into_iter()
+                .map(|(k, l)| (k.map(|k| ba[k].to_string()), l.map(|l| bb[l].to_string())))
+                .filter(|(x, y)| x != y)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        diffs.push(Difference { kind, a: i, b: j, blocks });
+    }
+
+    diffs
+}
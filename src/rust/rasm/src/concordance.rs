@@ -0,0 +1,120 @@
+/*
+ *    concordance.rs
+ *
+ * keyword in context concordance of rasm words and letterblocks
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::quote;
+use crate::search::Pattern;
+
+/// Word of a text or of the mushaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Word {
+    /// original token
+    pub ori: String,
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub lat: String,
+    /// [line] of a text or [sura, vers, word] of the mushaf, starting in 1
+    pub pos: Vec<usize>,
+}
+
+/// Occurrence of a query with its context.
+#[derive(Debug, Clone, Copy)]
+pub struct Kwic<'a> {
+    /// word that matched, or that contains the matching block
+    pub word: &'a Word,
+    /// number of the matching block inside the word, starting in 1, if blocks are searched
+    pub block: Option<usize>,
+    /// preceding words, in order
+    pub left: &'a [Word],
+    /// following words, in order
+    pub right: &'a [Word],
+}
+
+/// Arabic words of text with their line numbers.
+pub fn text_words(conv: &Converter, text: &str) -> Vec<Word> {
+    quote::text_tokens(conv, text).into_iter()
+        .map(|tok| Word {
+            lat: conv.reduce(&conv.normalise(&tok.ori)).lat,
+            ori: tok.ori,
+            pos: vec![tok.line],
+        })
+        .collect()
+}
+
+/// Words of the mushaf with their indexes. Words with no archigraphemes (۞ ۩) are left out.
+pub fn mushaf_words(conv: &Converter, quran: &Mushaf) -> Vec<Word> {
+    quran.words()
+        .map(|((sura, vers, word), blocks)| Word {
+            ori: blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect(),
+            lat: blocks.iter()
+                .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+                .filter(|b| !b.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            pos: vec![sura, vers, word],
+        })
+        .filter(|word| !word.lat.is_empty())
+        .collect()
+}
+
+/// Occurrences of pattern in words with up to context words on each side. If blocks is true, pattern is
+/// matched against each letterblock instead of whole words, so a word may appear once per matching block.
+/// Only words for which keep returns true are searched, but any word may appear in the context.
+pub fn concordance<'a, F>(words: &'a [Word], pattern: &Pattern, blocks: bool, context: usize, keep: F) -> Vec<Kwic<'a>>
+where
+    F: Fn(&Word) -> bool,
+{
+    let mut hits = Vec::new();
+
+    for (i, word) in words.iter().enumerate() {
+        if !keep(word) {
+            continue;
+        }
+        let matches: Vec<Option<usize>> = if blocks {
+            word.lat.split_whitespace().enumerate()
+                .filter(|(_, block)| pattern.is_match(block))
+                .map(|(iblock, _)| Some(iblock+1))
+                .collect()
+        } else if pattern.is_match(&word.lat) {
+            vec![None]
+        } else {
+            vec![]
+        };
+        for block in matches {
+            hits.push(Kwic {
+                word,
+                block,
+                left: &words[i.saturating_sub(context)..i],
+                right: &words[i+1..(i+1+context).min(words.len())],
+            });
+        }
+    }
+
+    hits
+}
@@ -0,0 +1,291 @@
+/*
+ *    convert.rs
+ *
+ * conversion of Arabic-scripted tokens into archigraphemes and paleo-orthographic representation
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use regex::{Captures, Regex};
+
+use crate::inventory::{Arachars, Mappings, load_arabic_inventory, load_rasm_mappings};
+
+/// Token or letterblock together with its archigraphemic representations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reduced {
+    /// original Arabic-scripted text
+    pub ori: String,
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub lat: String,
+    /// archigraphemes in Arabic script, blocks separated by spaces
+    pub ara: String,
+    /// paleo-orthographic representation, empty unless converted with `Converter::paleo`
+    pub pal: String,
+}
+
+/// Inventory, mappings and compiled regexes needed to convert Arabic script into archigraphemes.
+pub struct Converter {
+    pub chars: Arachars,
+    pub mappings: Mappings,
+    letters: HashSet<char>,
+    ardw: HashSet<char>,
+    pub re_norm: Regex,
+    pub re_ara: Regex,
+    pub re_clean: Regex,
+    pub re_rasm_end: Regex,
+    pub re_rasm_gen: Regex,
+    pub re_rasm_ara: Regex,
+    pub re_rasm_pal: Regex,
+    pub re_tanwin: Regex,
+    pub re_ardw: Regex,
+    pub re_lat: Regex,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Converter::new()
+    }
+}
+
+impl Converter {
+
+    pub fn new() -> Converter {
+
+        let chars = load_arabic_inventory();
+        let mappings = load_rasm_mappings(&chars);
+
+        let letters = chars.letters().chars().collect();
+        let ardw = [chars.A, chars.R, chars.D, chars.W].concat().chars().collect();
+
+        let re_norm = Regex::new(&chars.clusters.keys().copied().collect::<Vec<_>>().join("|")).unwrap();
+
+        let re_ara = Regex::new(&format!("[^{letters}{diac} ]", letters=chars.letters(), diac=chars.diac)).unwrap();
+
+        let re_clean = Regex::new(&format!("[{}]", chars.diac)).unwrap();
+
+        let re_rasm_end = Regex::new(&format!("({})$", mappings.mapping_end.keys().map(|s| &**s).collect::<Vec<_>>().join("|"))).unwrap();
+
+        let re_rasm_gen = Regex::new(&mappings.mapping_gen.keys().map(|s| &**s).collect::<Vec<_>>().join("|")).unwrap();
+        let re_rasm_ara = Regex::new(&mappings.mapping_ara.keys().copied().collect::<Vec<_>>().join("|")).unwrap();
+
+        // longest keys first, so that decomposed sequences win over their single characters
+        let mut keys_pal = mappings.mapping_pal.keys().copied().collect::<Vec<_>>();
+        keys_pal.sort_by_key(|k| std::cmp::Reverse(k.chars().count()));
+        let re_rasm_pal = Regex::new(&keys_pal.iter().map(|k| regex::escape(k)).collect::<Vec<_>>().join("|")).unwrap();
+
+        let re_tanwin = Regex::new("ََ|ُُ|ِِ").unwrap();
+
+        // separate blocks in archigraphemic representation
+        let re_ardw = Regex::new("([ARDW][^QNYABGRDTCSFEWHMLK]*)").unwrap();
+
+        let re_lat = Regex::new("[^A-Y ]").unwrap();
+
+        Converter { chars, mappings, letters, ardw, re_norm, re_ara, re_clean, re_rasm_end, re_rasm_gen,
+                    re_rasm_ara, re_rasm_pal, re_tanwin, re_ardw, re_lat }
+    }
+
+    /// Normalise clusters of Arabic Presentation Forms (FB50–FDFF, FE70–FEFF).
+    pub fn normalise<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        self.re_norm.replace_all(line, |cap: &Captures| {
+            self.chars.clusters[cap.get(0).unwrap().as_str()]
+        })
+    }
+
+    /// Normalise doubled short vowels into tanwin.
+    pub fn normalise_tanwin<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        self.re_tanwin.replace_all(line, |cap: &Captures| {
+            match cap.get(0).unwrap().as_str() {
+                "ََ" => "ً",
+                "ُُ" => "ٌ",
+                _ => "ٍ",
+            }
+        })
+    }
+
+    /// Split line into tokens at whitespace and punctuation.
+    pub fn tokenise<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        line.split(|c: char| c.is_ascii_whitespace() || c.is_ascii_punctuation() || "؟،؛".contains(c))
+            .filter(|tok| !tok.is_empty())
+    }
+
+    pub fn is_letter(&self, c: char) -> bool {
+        self.letters.contains(&c)
+    }
+
+    /// Convert token without diacritics into archigraphemes.
+    pub fn reduce(&self, tok: &str) -> Reduced {
+
+        let tok_clean = self.re_clean.replace_all(tok, "");
+
+        let tok_reduced_lat = self.re_rasm_end.replace_all(&tok_clean, |cap: &Captures| {
+            self.mappings.mapping_end[cap.get(0).unwrap().as_str()].clone()
+        });
+
+        let tok_reduced_lat = self.re_rasm_gen.replace_all(&tok_reduced_lat, |cap: &Captures| {
+            self.mappings.mapping_gen[cap.get(0).unwrap().as_str()].clone()
+        });
+
+        let tok_reduced_lat = self.re_ardw.replace_all(&tok_reduced_lat, "$1 ");
+
+        let tok_reduced_ara = self.to_arabic(&tok_reduced_lat);
+
+        Reduced {
+            ori: tok.to_string(),
+            lat: tok_reduced_lat.trim_end().to_string(),
+            ara: tok_reduced_ara.trim_end().to_string(),
+            pal: String::new(),
+        }
+    }
+
+    /// Convert token into paleo-orthographic representation, keeping also its bare archigraphemes.
+    pub fn paleo(&self, tok: &str, unstable_alif: bool) -> Reduced {
+
+        // convert to paleo general
+        let pal = self.re_rasm_pal.replace_all(tok, |cap: &Captures| {
+            self.mappings.mapping_pal[cap.get(0).unwrap().as_str()]
+        });
+
+        // restore consonantal diacritics for ya when appropriate
+        let mut pal: Vec<char> = pal.chars().collect();
+        let mut i = 0;
+        while i < pal.len() {
+            if (pal[i] == 'ی' || pal[i] == 'ى')
+                && !matches!(pal.get(i+1), Some('₂') | Some('ɂ') | Some('ˀ') | Some('ᴬ'))
+                && pal[i+1..].iter().any(|c| ('\u{0627}'..='\u{06cc}').contains(c)) {
+                pal.splice(i..=i, vec!['ی', '₂']);
+                i += 1;
+            }
+            i += 1;
+        }
+
+        // convert to paleo NQY
+        let final_letter = |pal: &[char], extra: &str| pal.iter().rposition(|&c| self.is_letter(c) || extra.contains(c));
+
+        if let Some(i) = final_letter(&pal, "") {
+            if self.chars.N.contains(pal[i]) { pal[i] = 'N' }
+        }
+        if let Some(i) = final_letter(&pal, "N") {
+            if self.chars.Q.contains(pal[i]) { pal[i] = 'Q' }
+        }
+        if let Some(i) = final_letter(&pal, "NQ") {
+            if self.chars.Y.contains(pal[i]) || self.chars.J.contains(pal[i]) { pal[i] = 'Y' }
+        }
+
+        // convert graphemes to rasm
+        let pal: String = pal.into_iter().collect();
+        let pal = self.re_rasm_gen.replace_all(&pal, |cap: &Captures| {
+            self.mappings.mapping_gen[cap.get(0).unwrap().as_str()].clone()
+        });
+
+        let pal = if unstable_alif { remove_unstable_alif(&pal) } else { pal.into_owned() };
+
+        let pal = self.re_ardw.replace_all(&pal, "$1 ");
+
+        // make copy with only archigraphemes
+        let lat = self.strip_paleo(&pal);
+        let ara = self.to_arabic(&lat);
+
+        Reduced {
+            ori: tok.to_string(),
+            lat: lat.trim_end().to_string(),
+            ara: ara.trim_end().to_string(),
+            pal: pal.trim_end().to_string(),
+        }
+    }
+
+    /// Keep only the Latin archigraphemes of a paleo-orthographic representation.
+    pub fn strip_paleo<'a>(&self, pal: &'a str) -> Cow<'a, str> {
+        self.re_lat.replace_all(pal, "")
+    }
+
+    /// Render Latin archigraphemes in Arabic script.
+    pub fn to_arabic<'a>(&self, lat: &'a str) -> Cow<'a, str> {
+        self.re_rasm_ara.replace_all(lat, |cap: &Captures| {
+            self.mappings.mapping_ara[cap.get(0).unwrap().as_str()]
+        })
+    }
+
+    /// Split Arabic-scripted token into letterblocks, i.e. after each A, R, D or W and the signs that follow it.
+    pub fn split_blocks<'a>(&self, tok: &'a str) -> Vec<&'a str> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        let mut closing = false;
+        for (i, c) in tok.char_indices() {
+            if closing && self.is_letter(c) {
+                blocks.push(&tok[start..i]);
+                start = i;
+                closing = false;
+            }
+            if self.ardw.contains(&c) {
+                closing = true;
+            }
+        }
+        if start < tok.len() {
+            blocks.push(&tok[start..]);
+        }
+        blocks
+    }
+
+    /// Split converted word into its letterblocks.
+    pub fn blocks(&self, word: &Reduced) -> Vec<Reduced> {
+        self.split_blocks(&word.ori).into_iter()
+            .zip(word.lat.split_whitespace())
+            .zip(word.ara.split_whitespace())
+            .zip(word.pal.split_whitespace().map(Some).chain(std::iter::repeat(None)))
+            .map(|(((ori, lat), ara), pal)| Reduced {
+                ori: ori.to_string(),
+                lat: lat.to_string(),
+                ara: ara.to_string(),
+                pal: pal.unwrap_or("").to_string(),
+            })
+            .collect()
+    }
+
+    /// Tokenise text and convert each token into letterblocks with paleo-orthographic representation.
+    pub fn paleo_blocks(&self, text: &str, unstable_alif: bool) -> Vec<Vec<Reduced>> {
+        let text = self.normalise_tanwin(text);
+        self.tokenise(&text)
+            .map(|tok| self.blocks(&self.paleo(tok, unstable_alif)))
+            .collect()
+    }
+}
+
+/// Delete fatha+alif in non final position.
+fn remove_unstable_alif(pal: &str) -> String {
+    let mut out = String::with_capacity(pal.len());
+    let mut rest = pal;
+    while let Some(i) = rest.find("ᵃA") {
+        let after = &rest[i+"ᵃA".len()..];
+        out.push_str(&rest[..i]);
+        if after.is_empty() || after.starts_with('\n') {
+            out.push_str("ᵃA");
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
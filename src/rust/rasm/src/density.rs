@@ -0,0 +1,119 @@
+/*
+ *    density.rs
+ *
+ * density of consonantal dots and diacritics relative to the archigraphemes
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use crate::convert::Converter;
+
+/// Names of the signs counted, in the order of Density::signs().
+pub const SIGNS: [&str; 6] = ["dots", "vowels", "shadda", "sukun", "hamza", "marks"];
+
+/// Number of archigraphemes of a token, line or document and of the signs added to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Density {
+    /// letters of the bare skeleton
+    pub archigraphemes: usize,
+    /// consonantal dots, eg. 2 for ت and 3 for ش
+    pub dots: usize,
+    /// fatha, damma, kasra and their tanwin forms
+    pub vowels: usize,
+    pub shadda: usize,
+    pub sukun: usize,
+    /// hamza on its own, above or below a letter
+    pub hamza: usize,
+    /// quranic annotation marks: madda, dagger alif, wasla, small letters, pause and stop signs
+    pub marks: usize,
+}
+
+impl Density {
+
+    /// Count the signs of an Arabic token. Dots, hamzas and wasla are taken from its paleo-orthographic
+    /// representation, so that letters with dots, hamza or wasla are counted as well as combining signs.
+    pub fn of_token(conv: &Converter, tok: &str) -> Density {
+
+        let tok = conv.normalise(tok);
+        let tok = conv.normalise_tanwin(&tok);
+        let pal = conv.paleo(&conv.re_ara.replace_all(&tok, ""), false).pal;
+
+        let mut density = Density::default();
+
+        for c in pal.chars() {
+            match c {
+                'A'..='Y' => density.archigraphemes += 1,
+                'ˀ' | 'ɂ' | 'ʔ' => density.hamza += 1,
+                'ᵟ' => density.marks += 1,
+                _ => density.dots += dots(c),
+            }
+        }
+
+        for c in tok.chars() {
+            match c {
+                '\u{064b}'..='\u{0650}' | '\u{08f0}'..='\u{08f2}' => density.vowels += 1,
+                '\u{0651}' => density.shadda += 1,
+                '\u{0652}' | '\u{06e1}' => density.sukun += 1,
+                '\u{0653}' | '\u{0670}' | '\u{06d6}'..='\u{06ed}' => density.marks += 1,
+                _ => {},
+            }
+        }
+
+        density
+    }
+
+    /// Add the counts of other.
+    pub fn add(&mut self, other: &Density) {
+        self.archigraphemes += other.archigraphemes;
+        self.dots += other.dots;
+        self.vowels += other.vowels;
+        self.shadda += other.shadda;
+        self.sukun += other.sukun;
+        self.hamza += other.hamza;
+        self.marks += other.marks;
+    }
+
+    /// Counts of the signs, in the order of SIGNS.
+    pub fn signs(&self) -> [usize; 6] {
+        [self.dots, self.vowels, self.shadda, self.sukun, self.hamza, self.marks]
+    }
+
+    /// Counts of the signs divided by the number of archigraphemes, in the order of SIGNS; 0 if there are none.
+    pub fn ratios(&self) -> [f64; 6] {
+        let arch = self.archigraphemes.max(1) as f64;
+        self.signs().map(|n| n as f64 / arch)
+    }
+}
+
+/// Number of dots encoded by a paleo-orthographic sign, eg. ² for two dots above and ’, the older
+/// spelling of ¹, for one.
+fn dots(c: char) -> usize {
+    match c {
+        '¹' | '₁' | '’' => 1,
+        '²' | '₂' | '۲' => 2,
+        '³' | '₃' | '۳' => 3,
+        '⁴' | '₄' | '۴' => 4,
+        _ => 0,
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ *    dotting.rs
+ *
+ * dotting candidates: dotted consonantal strings that a rasm skeleton may represent
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::prelude::*;
+use std::io::Result;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Standard Arabic letters each archigrapheme stands for, in non-final and in word-final position.
+/// Final ن, ق and ي/ى are written with the final-only archigraphemes N, Q and Y, so B and F do not
+/// include them at the end of a word, and ة is only possible at the end.
+fn letters(arch: char, last: bool) -> Option<&'static [char]> {
+    let letters: &'static [char] = match (arch, last) {
+        ('A', _) => &['ا'],
+        ('B', false) => &['ب', 'ت', 'ث', 'ن', 'ي'],
+        ('B', true) => &['ب', 'ت', 'ث'],
+        ('G', _) => &['ج', 'ح', 'خ'],
+        ('R', _) => &['ر', 'ز'],
+        ('D', _) => &['د', 'ذ'],
+        ('T', _) => &['ط', 'ظ'],
+        ('C', _) => &['ص', 'ض'],
+        ('S', _) => &['س', 'ش'],
+        ('F', false) => &['ف', 'ق'],
+        ('F', true) => &['ف'],
+        ('E', _) => &['ع', 'غ'],
+        ('W', _) => &['و'],
+        ('H', false) => &['ه'],
+        ('H', true) => &['ه', 'ة'],
+        ('O', _) => &['ھ'],
+        ('M', _) => &['م'],
+        ('L', _) => &['ل'],
+        ('K', _) => &['ك'],
+        ('Q', true) => &['ق'],
+        ('N', true) => &['ن'],
+        ('Y', true) => &['ي', 'ى'],
+        ('J', true) => &['ے'],
+        _ => return None,
+    };
+    Some(letters)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DottingError {
+    /// query with no archigraphemes, eg. empty or only vowels
+    Empty { query: String },
+    /// character that is not an archigrapheme
+    Invalid { rasm: String, pos: usize, c: char },
+    /// Q, N, Y or J not at the end of the word
+    NotFinal { rasm: String, pos: usize, c: char },
+    /// more candidates than the given limit; no count if it overflows
+    TooMany { rasm: String, count: Option<usize>, limit: usize },
+}
+
+impl fmt::Display for DottingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DottingError::Empty { query } =>
+                write!(f, "invalid query \"{}\": it has no archigraphemes", query),
+            DottingError::Invalid { rasm, pos, c } =>
+                write!(f, "invalid rasm \"{}\": '{}' at position {} is not an archigrapheme", rasm, c, pos),
+            DottingError::NotFinal { rasm, pos, c } =>
+                write!(f, "invalid rasm \"{}\": '{}' at position {} can only appear at the end of a word", rasm, c, pos),
+            DottingError::TooMany { rasm, count, limit } => {
+                let count = count.map_or("overflow".to_string(), |n| n.to_string());
+                write!(f, "rasm \"{}\" has {} candidates, more than the limit of {}; use a lexicon", rasm, count, limit)
+            }
+        }
+    }
+}
+
+impl Error for DottingError {}
+
+/// Reduce query to Latin rasm without spaces. Arabic script, dotted or not, is reduced into archigraphemes.
+pub fn query_rasm(conv: &Converter, query: &str) -> String {
+    if query.is_ascii() {
+        query.split_whitespace().collect()
+    } else {
+        conv.reduce(&conv.normalise(query)).lat.split_whitespace().collect()
+    }
+}
+
+/// Letters possible at each position of Latin rasm, checking that final-only archigraphemes are final.
+fn positions(rasm: &str) -> std::result::Result<Vec<&'static [char]>, DottingError> {
+    if rasm.is_empty() {
+        return Err(DottingError::Empty { query: rasm.to_string() });
+    }
+    let chars: Vec<char> = rasm.chars().collect();
+    chars.iter().enumerate().map(|(i, &c)| {
+        let last = i+1 == chars.len();
+        letters(c, last).ok_or_else(|| match letters(c, true) {
+            Some(_) => DottingError::NotFinal { rasm: rasm.to_string(), pos: i+1, c },
+            None => DottingError::Invalid { rasm: rasm.to_string(), pos: i+1, c },
+        })
+    }).collect()
+}
+
+/// Generate every dotted consonantal string that Latin rasm, without spaces, may represent.
+/// Fail if there are more than limit candidates.
+pub fn candidates(rasm: &str, limit: usize) -> std::result::Result<Vec<String>, DottingError> {
+
+    let positions = positions(rasm)?;
+
+    let count = positions.iter().try_fold(1usize, |n, p| n.checked_mul(p.len()));
+    if count.is_none_or(|n| n > limit) {
+        return Err(DottingError::TooMany { rasm: rasm.to_string(), count, limit });
+    }
+
+    let mut cands = vec![String::new()];
+    for letters in positions {
+        cands = cands.iter()
+            .flat_map(|cand| letters.iter().map(move |&c| format!("{}{}", cand, c)))
+            .collect();
+    }
+    Ok(cands)
+}
+
+/// Word list indexed by rasm, used to keep only the attested candidates.
+#[derive(Debug, Default)]
+pub struct Lexicon {
+    /// rasm without spaces -> dotted form -> frequency
+    forms: HashMap<String, HashMap<String, usize>>,
+}
+
+impl Lexicon {
+
+    pub fn new() -> Lexicon {
+        Lexicon::default()
+    }
+
+    /// Add one occurrence of word. Diacritics are removed, so only its dotted consonants are kept.
+    pub fn add(&mut self, conv: &Converter, word: &str) {
+        let reduced = conv.reduce(word);
+        if reduced.lat.is_empty() {
+            return;
+        }
+        let rasm: String = reduced.lat.split_whitespace().collect();
+        let dotted = conv.re_clean.replace_all(word, "").into_owned();
+        *self.forms.entry(rasm).or_default().entry(dotted).or_insert(0) += 1;
+    }
+
+    /// Read word list, with whitespace-separated Arabic words.
+    pub fn from_words<R: BufRead>(conv: &Converter, words: R) -> Result<Lexicon> {
+        let mut lexicon = Lexicon::new();
+        for line in words.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            for word in line.split_whitespace() {
+                lexicon.add(conv, word);
+            }
+        }
+        Ok(lexicon)
+    }
+
+    /// Vocabulary of the Quran, optionally only inside range.
+    pub fn from_mushaf(conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>) -> Lexicon {
+        let mut lexicon = Lexicon::new();
+        for ((sura, vers, word), blocks) in quran.words() {
+            if range.is_some_and(|range| !range.contains(&[sura, vers, word])) {
+                continue;
+            }
+            let word: String = blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect();
+            lexicon.add(conv, &word);
+        }
+        lexicon
+    }
+
+    /// Attested dotted forms of Latin rasm, without spaces, most frequent first.
+    pub fn candidates(&self, rasm: &str) -> std::result::Result<Vec<(String, usize)>, DottingError> {
+        positions(rasm)?;
+        let mut found: Vec<(String, usize)> = self.forms.get(rasm)
+            .map(|forms| forms.iter().map(|(form, &n)| (form.clone(), n)).collect())
+            .unwrap_or_default();
+        found.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(found)
+    }
+}
@@ -0,0 +1,521 @@
+/*
+ *    encoding.rs
+ *
+ * detection and decoding of the legacy encodings of Arabic text: Windows-1256, ISO-8859-6 and UTF-16
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+/// Characters of the bytes 0x80-0xFF in Windows-1256, which defines all of them.
+const CP1256: [char; 128] = [
+    '\u{20ac}', '\u{067e}', '\u{201a}', '\u{0192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02c6}', '\u{2030}', '\u{0679}', '\u{2039}', '\u{0152}', '\u{0686}', '\u{0698}', '\u{0688}',
+    '\u{06af}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{06a9}', '\u{2122}', '\u{0691}', '\u{203a}', '\u{0153}', '\u{200c}', '\u{200d}', '\u{06ba}',
+    '\u{00a0}', '\u{060c}', '\u{00a2}', '\u{00a3}', '\u{00a4}', '\u{00a5}', '\u{00a6}', '\u{00a7}',
+    '\u{00a8}', '\u{00a9}', '\u{06be}', '\u{00ab}', '\u{00ac}', '\u{00ad}', '\u{00ae}', '\u{00af}',
+    '\u{00b0}', '\u{00b1}', '\u{00b2}', '\u{00b3}', '\u{00b4}', '\u{00b5}', '\u{00b6}', '\u{00b7}',
+    '\u{00b8}', '\u{00b9}', '\u{061b}', '\u{00bb}', '\u{00bc}', '\u{00bd}', '\u{00be}', '\u{061f}',
+    '\u{06c1}', '\u{0621}', '\u{0622}', '\u{0623}', '\u{0624}', '\u{0625}', '\u{0626}', '\u{0627}',
+    '\u{0628}', '\u{0629}', '\u{062a}', '\u{062b}', '\u{062c}', '\u{062d}', '\u{062e}', '\u{062f}',
+    '\u{0630}', '\u{0631}', '\u{0632}', '\u{0633}', '\u{0634}', '\u{0635}', '\u{0636}', '\u{00d7}',
+    '\u{0637}', '\u{0638}', '\u{0639}', '\u{063a}', '\u{0640}', '\u{0641}', '\u{0642}', '\u{0643}',
+    '\u{00e0}', '\u{0644}', '\u{00e2}', '\u{0645}', '\u{0646}', '\u{0647}', '\u{0648}', '\u{00e7}',
+    '\u{00e8}', '\u{00e9}', '\u{00ea}', '\u{00eb}', '\u{0649}', '\u{064a}', '\u{00ee}', '\u{00ef}',
+    '\u{064b}', '\u{064c}', '\u{064d}', '\u{064e}', '\u{00f4}', '\u{064f}', '\u{0650}', '\u{00f7}',
+    '\u{0651}', '\u{00f9}', '\u{0652}', '\u{00fb}', '\u{00fc}', '\u{200e}', '\u{200f}', '\u{06d2}',
+];
+
+/// Encoding of an input text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Cp1256,
+    Iso8859_6,
+}
+
+impl Encoding {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+            Encoding::Cp1256 => "cp1256",
+            Encoding::Iso8859_6 => "iso-8859-6",
+        }
+    }
+
+    /// Byte order mark of the encoding, empty for the single-byte encodings.
+    fn bom(&self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => &[0xef, 0xbb, 0xbf],
+            Encoding::Utf16Le => &[0xff, 0xfe],
+            Encoding::Utf16Be => &[0xfe, 0xff],
+            Encoding::Cp1256 | Encoding::Iso8859_6 => &[],
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "utf-16le" | "utf16le" => Ok(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Encoding::Utf16Be),
+            "cp1256" | "windows-1256" => Ok(Encoding::Cp1256),
+            "iso-8859-6" | "iso8859-6" => Ok(Encoding::Iso8859_6),
+            _ => Err(format!("unknown encoding \"{}\", expected utf-8, utf-16le, utf-16be, cp1256 or iso-8859-6", s)),
+        }
+    }
+}
+
+/// Sequence of bytes that is not valid in the encoding of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Undecodable {
+    pub encoding: Encoding,
+    /// line of the input, starting in 1
+    pub line: usize,
+    /// offset of the first byte from the start of the input, starting in 0
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl fmt::Display for Undecodable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex: Vec<String> = self.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+        write!(f, "line {}, byte {}: cannot decode {} as {}", self.line, self.offset, hex.join(" "), self.encoding)
+    }
+}
+
+impl Error for Undecodable {}
+
+/// Text decoded from bytes, with U+FFFD in place of each undecodable sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub text: String,
+    pub errors: Vec<Undecodable>,
+}
+
+/// Accumulates decoded characters and keeps count of the lines.
+struct Writer {
+    encoding: Encoding,
+    line: usize,
+    decoded: Decoded,
+    /// length of the text where each error was found
+    error_at: Vec<usize>,
+}
+
+impl Writer {
+
+    fn new(encoding: Encoding) -> Writer {
+        Writer { encoding, line: 1, decoded: Decoded { text: String::new(), errors: vec![] }, error_at: vec![] }
+    }
+
+    fn push(&mut self, c: char) {
+        if c == '\n' {
+            self.line += 1;
+        }
+        self.decoded.text.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.line += s.matches('\n').count();
+        self.decoded.text.push_str(s);
+    }
+
+    fn fail(&mut self, offset: usize, bytes: &[u8]) {
+        self.decoded.errors.push(Undecodable { encoding: self.encoding, line: self.line, offset, bytes: bytes.to_vec() });
+        self.error_at.push(self.decoded.text.len());
+        self.decoded.text.push(char::REPLACEMENT_CHARACTER);
+    }
+}
+
+/// Character of a byte in ISO-8859-6, which leaves the C1 controls and most of the upper half undefined.
+fn iso8859_6(b: u8) -> Option<char> {
+    match b {
+        0x00..=0x7f => Some(b as char),
+        0xa0 | 0xa4 | 0xad => Some(b as char),
+        0xac => Some('\u{060c}'),
+        0xbb => Some('\u{061b}'),
+        0xbf => Some('\u{061f}'),
+        0xc1..=0xda | 0xe0..=0xf2 => char::from_u32(0x0560 + b as u32),
+        _ => None,
+    }
+}
+
+/// Decode bytes starting at offset `base` of the input into `out`. Unless `last`, a sequence cut at the end
+/// is left undecoded for the next call. Returns the number of bytes consumed.
+fn decode_into(bytes: &[u8], base: usize, last: bool, out: &mut Writer) -> usize {
+
+    let mut pos = 0;
+
+    match out.encoding {
+        Encoding::Utf8 => {
+            while pos < bytes.len() {
+                match std::str::from_utf8(&bytes[pos..]) {
+                    Ok(s) => {
+                        out.push_str(s);
+                        pos = bytes.len();
+                    }
+                    Err(err) => {
+                        let valid = err.valid_up_to();
+                        out.push_str(std::str::from_utf8(&bytes[pos..pos+valid]).unwrap());
+                        pos += valid;
+                        let len = match err.error_len() {
+                            Some(len) => len,
+                            None if !last => break,
+                            None => bytes.len() - pos,
+                        };
+                        out.fail(base+pos, &bytes[pos..pos+len]);
+                        pos += len;
+                    }
+                }
+            }
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let little = out.encoding == Encoding::Utf16Le;
+            let unit = |i: usize| if little {
+                u16::from_le_bytes([bytes[i], bytes[i+1]])
+            } else {
+                u16::from_be_bytes([bytes[i], bytes[i+1]])
+            };
+            while pos + 2 <= bytes.len() {
+                let high = unit(pos);
+                if !(0xd800..0xdc00).contains(&high) {
+                    match char::from_u32(high as u32) {
+                        Some(c) => out.push(c),
+                        None => out.fail(base+pos, &bytes[pos..pos+2]),
+                    }
+                    pos += 2;
+                } else if pos + 4 > bytes.len() && !last {
+                    break;
+                } else if pos + 4 <= bytes.len() && (0xdc00..0xe000).contains(&unit(pos+2)) {
+                    let low = unit(pos+2);
+                    out.push(char::from_u32(0x10000 + ((high as u32 - 0xd800) << 10) + (low as u32 - 0xdc00)).unwrap());
+                    pos += 4;
+                } else {
+                    out.fail(base+pos, &bytes[pos..pos+2]);
+                    pos += 2;
+                }
+            }
+            // odd number of bytes
+            if pos < bytes.len() && last {
+                out.fail(base+pos, &bytes[pos..]);
+                pos = bytes.len();
+            }
+        }
+        Encoding::Cp1256 => {
+            for &b in bytes {
+                out.push(if b < 0x80 { b as char } else { CP1256[b as usize - 0x80] });
+            }
+            pos = bytes.len();
+        }
+        Encoding::Iso8859_6 => {
+            for (i, &b) in bytes.iter().enumerate() {
+                match iso8859_6(b) {
+                    Some(c) => out.push(c),
+                    None => out.fail(base+i, &bytes[i..i+1]),
+                }
+            }
+            pos = bytes.len();
+        }
+    }
+
+    pos
+}
+
+/// Decode bytes, skipping the byte order mark of the encoding if present. Each undecodable sequence is
+/// replaced and reported with its line and byte offset.
+pub fn decode(bytes: &[u8], encoding: Encoding) -> Decoded {
+    let bom = if bytes.starts_with(encoding.bom()) { encoding.bom().len() } else { 0 };
+    let mut out = Writer::new(encoding);
+    decode_into(&bytes[bom..], bom, true, &mut out);
+    out.decoded
+}
+
+/// Guess the encoding of the start of a text: the one of the byte order mark, else UTF-8 if most of its
+/// multibyte sequences are valid, else ISO-8859-6 if it can decode all bytes into more Arabic letters than
+/// Windows-1256, which otherwise is the default. A sequence cut at the end of the prefix does not count.
+pub fn detect(prefix: &[u8]) -> Encoding {
+
+    for encoding in [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be] {
+        if prefix.starts_with(encoding.bom()) {
+            return encoding;
+        }
+    }
+
+    let mut utf8 = Writer::new(Encoding::Utf8);
+    decode_into(prefix, 0, false, &mut utf8);
+    let utf8 = utf8.decoded;
+    let multibyte = utf8.text.chars().filter(|&c| c.len_utf8() > 1 && c != char::REPLACEMENT_CHARACTER).count();
+    if utf8.errors.is_empty() || multibyte > utf8.errors.len() {
+        return Encoding::Utf8;
+    }
+
+    let arabic_letters = |text: &str| text.chars().filter(|c| matches!(c, '\u{0621}'..='\u{063a}' | '\u{0641}'..='\u{064a}')).count();
+
+    let iso = decode(prefix, Encoding::Iso8859_6);
+    if iso.errors.is_empty() && arabic_letters(&iso.text) > arabic_letters(&decode(prefix, Encoding::Cp1256).text) {
+        return Encoding::Iso8859_6;
+    }
+
+    Encoding::Cp1256
+}
+
+/// Bytes read to detect the encoding of an input.
+const DETECT_PREFIX: usize = 8192;
+
+/// Function that receives the undecodable sequences of a lossy decoder.
+pub type Report = dyn FnMut(&Undecodable);
+
+/// Reader that decodes its input as it goes and gives it out in UTF-8, so that it can be read by lines.
+/// An undecodable sequence is an error of kind InvalidData, unless the decoder is lossy, in which case it is
+/// replaced with U+FFFD and handed to the report function.
+pub struct Decoder<R: Read> {
+    inner: R,
+    /// bytes read and not decoded yet, with the offset of the first one in the input
+    pending: Vec<u8>,
+    offset: usize,
+    eof: bool,
+    out: Writer,
+    /// position of the next byte to give out of the decoded text
+    pos: usize,
+    report: Option<Box<Report>>,
+    /// error to give once the text decoded before it has been read
+    failure: Option<Undecodable>,
+}
+
+impl<R: Read> Decoder<R> {
+
+    /// Decoder of the input in the given encoding, or in the one detected from its first bytes.
+    pub fn new(mut inner: R, encoding: Option<Encoding>) -> io::Result<Decoder<R>> {
+
+        let mut pending = vec![];
+        let mut eof = false;
+
+        if encoding.is_none() {
+            let mut chunk = [0u8; DETECT_PREFIX];
+            while !eof && pending.len() < DETECT_PREFIX {
+                match inner.read(&mut chunk[pending.len()..]) {
+                    Ok(0) => eof = true,
+                    Ok(n) => pending.extend_from_slice(&chunk[pending.len()..pending.len()+n]),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        let encoding = encoding.unwrap_or_else(|| detect(&pending));
+
+        let mut decoder = Decoder { inner, pending, offset: 0, eof, out: Writer::new(encoding), pos: 0, report: None,
+                                   failure: None };
+
+        // the byte order mark may still have to be read
+        while !decoder.eof && decoder.pending.len() < encoding.bom().len() {
+            decoder.read_chunk()?;
+        }
+        if decoder.pending.starts_with(encoding.bom()) {
+            decoder.pending.drain(..encoding.bom().len());
+            decoder.offset = encoding.bom().len();
+        }
+
+        Ok(decoder)
+    }
+
+    /// Replace undecodable sequences instead of failing, handing each one to report.
+    pub fn lossy<F: FnMut(&Undecodable) + 'static>(mut self, report: F) -> Decoder<R> {
+        self.report = Some(Box::new(report));
+        self
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.out.encoding
+    }
+
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; DETECT_PREFIX];
+        loop {
+            match self.inner.read(&mut chunk) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+            return Ok(());
+        }
+    }
+
+    /// Read and decode the next chunk of the input.
+    fn fill(&mut self) -> io::Result<()> {
+
+        if !self.eof {
+            self.read_chunk()?;
+        }
+
+        let consumed = decode_into(&self.pending, self.offset, self.eof, &mut self.out);
+        self.pending.drain(..consumed);
+        self.offset += consumed;
+
+        let errors: Vec<Undecodable> = self.out.decoded.errors.drain(..).collect();
+        let error_at = std::mem::take(&mut self.out.error_at);
+
+        match self.report.as_mut() {
+            Some(report) => errors.iter().for_each(report),
+            None => if let Some(err) = errors.into_iter().next() {
+                // give out the text up to the error, and nothing after it
+                self.out.decoded.text.truncate(error_at[0]);
+                self.failure = Some(err);
+                self.pending.clear();
+                self.eof = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+
+        while self.pos == self.out.decoded.text.len() {
+            if let Some(err) = self.failure.take() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+            }
+            if self.eof && self.pending.is_empty() {
+                return Ok(0);
+            }
+            self.out.decoded.text.clear();
+            self.pos = 0;
+            self.fill()?;
+        }
+
+        let text = &self.out.decoded.text.as_bytes()[self.pos..];
+        let n = text.len().min(buf.len());
+        buf[..n].copy_from_slice(&text[..n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{BufRead, BufReader};
+
+    use super::*;
+
+    /// Encode text in a single-byte encoding by searching the byte of each character.
+    fn encode(text: &str, encoding: Encoding) -> Vec<u8> {
+        text.chars().map(|c| (0..=255u8).find(|&b| decode(&[b], encoding).text == c.to_string()).unwrap()).collect()
+    }
+
+    #[test]
+    fn detect_legacy() {
+        let text = "بِسْمِ اللَّهِ الرَّحْمَنِ الرَّحِيمِ\nالْحَمْدُ لِلَّهِ رَبِّ الْعَالَمِينَ";
+        for encoding in [Encoding::Cp1256, Encoding::Iso8859_6] {
+            let bytes = encode(text, encoding);
+            assert_eq!(detect(&bytes), encoding);
+            assert_eq!(decode(&bytes, encoding).text, text);
+        }
+        let mut utf16: Vec<u8> = vec![0xff, 0xfe];
+        utf16.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+        assert_eq!(detect(&utf16), Encoding::Utf16Le);
+        assert_eq!(decode(&utf16, Encoding::Utf16Le).text, text);
+        assert_eq!(detect(text.as_bytes()), Encoding::Utf8);
+    }
+
+    #[test]
+    fn report_undecodable() {
+        let mut bytes = "بسم\nال".as_bytes().to_vec();
+        bytes.push(0xd9);
+        bytes.extend("له".as_bytes());
+        let decoded = decode(&bytes, Encoding::Utf8);
+        assert_eq!(decoded.text, "بسم\nال\u{fffd}له");
+        assert_eq!(decoded.errors, vec![Undecodable { encoding: Encoding::Utf8, line: 2, offset: 11, bytes: vec![0xd9] }]);
+    }
+
+    /// Reader that gives out one byte at a time, so that every sequence gets cut between chunks.
+    struct ByteByByte<'a>(&'a [u8]);
+
+    impl Read for ByteByByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn stream_in_chunks() {
+        let text = "بسم 𝔸 الله\nالرحمن";
+        let mut utf16: Vec<u8> = vec![0xfe, 0xff];
+        utf16.extend(text.encode_utf16().flat_map(|u| u.to_be_bytes()));
+        for bytes in [text.as_bytes().to_vec(), utf16] {
+            let mut decoded = String::new();
+            Decoder::new(ByteByByte(&bytes), None).unwrap().read_to_string(&mut decoded).unwrap();
+            assert_eq!(decoded, text);
+        }
+    }
+
+    #[test]
+    fn stream_errors() {
+        let mut bytes = "بسم\nال".as_bytes().to_vec();
+        bytes.push(0xd9);
+        bytes.extend("له".as_bytes());
+
+        let err = Decoder::new(ByteByByte(&bytes), Some(Encoding::Utf8)).unwrap().read_to_string(&mut String::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2, byte 11: cannot decode D9 as utf-8");
+
+        // lines before the error are still read
+        let mut lines = BufReader::new(Decoder::new(&bytes[..], None).unwrap()).lines();
+        assert_eq!(lines.next().unwrap().unwrap(), "بسم");
+        assert!(lines.next().unwrap().is_err());
+
+        let mut decoded = String::new();
+        Decoder::new(ByteByByte(&bytes), Some(Encoding::Utf8)).unwrap().lossy(|_| {}).read_to_string(&mut decoded).unwrap();
+        assert_eq!(decoded, "بسم\nال\u{fffd}له");
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ *    homograph.rs
+ *
+ * homographs: dotted or vocalised tokens grouped by the rasm they share
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::io::Result;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Skeleton together with all the tokens that reduce to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Group {
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub rasm: String,
+    /// total number of occurrences of the skeleton
+    pub total: usize,
+    /// distinct realisations and their frequencies, most frequent first
+    pub realisations: Vec<(String, usize)>,
+}
+
+/// Frequencies of tokens grouped by their Latin rasm.
+#[derive(Debug, Default)]
+pub struct Homographs {
+    groups: HashMap<String, HashMap<String, usize>>,
+}
+
+impl Homographs {
+
+    pub fn new() -> Homographs {
+        Homographs::default()
+    }
+
+    /// Count one occurrence of tok with skeleton rasm.
+    pub fn add(&mut self, rasm: &str, tok: &str) {
+        *self.groups.entry(rasm.to_string()).or_default().entry(tok.to_string()).or_insert(0) += 1;
+    }
+
+    /// Add all Arabic tokens of text. If vocalised is false, diacritics are removed and only the dotted
+    /// consonantal form of each token is counted.
+    pub fn add_text<R: BufRead>(&mut self, conv: &Converter, text: R, vocalised: bool) -> Result<()> {
+        for line in text.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            for tok in line.split_whitespace() {
+                let reduced = conv.reduce(tok);
+                if reduced.lat.is_empty() {
+                    continue;
+                }
+                let tok = if vocalised { conv.normalise_tanwin(tok) } else { conv.re_clean.replace_all(tok, "") };
+                self.add(&reduced.lat, &tok);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add all words of the mushaf, optionally only those inside range. Words with no archigraphemes
+    /// (۞ ۩) are left out.
+    pub fn add_mushaf(&mut self, conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>, vocalised: bool) {
+        for ((sura, vers, word), blocks) in quran.words() {
+            if range.is_some_and(|range| !range.contains(&[sura, vers, word])) {
+                continue;
+            }
+            let rasm = blocks.iter()
+                .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+                .filter(|b| !b.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if rasm.is_empty() {
+                continue;
+            }
+            let tok: String = blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect();
+            let tok = if vocalised { tok } else { conv.re_clean.replace_all(&tok, "").into_owned() };
+            self.add(&rasm, &tok);
+        }
+    }
+
+    /// Realisations of skeleton rasm, with blocks separated by spaces.
+    pub fn get(&self, rasm: &str) -> Option<Group> {
+        self.groups.get(rasm).map(|toks| group(rasm, toks))
+    }
+
+    /// All groups sorted by number of realisations, then by frequency, then by rasm.
+    pub fn groups(&self) -> Vec<Group> {
+        let mut groups: Vec<Group> = self.groups.iter().map(|(rasm, toks)| group(rasm, toks)).collect();
+        groups.sort_by(|a, b| b.realisations.len().cmp(&a.realisations.len())
+            .then(b.total.cmp(&a.total))
+            .then(a.rasm.cmp(&b.rasm)));
+        groups
+    }
+}
+
+fn group(rasm: &str, toks: &HashMap<String, usize>) -> Group {
+    let mut realisations: Vec<(String, usize)> = toks.iter().map(|(tok, &n)| (tok.clone(), n)).collect();
+    realisations.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Group {
+        rasm: rasm.to_string(),
+        total: realisations.iter().map(|(_, n)| n).sum(),
+        realisations,
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ *    index.rs
+ *
+ * inverted index from rasm letterblocks and words to their positions in the Quran
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+
+/// Inverted index of a mushaf: Latin rasm of each block and word -> positions where it occurs.
+#[derive(Debug, Default)]
+pub struct RasmIndex {
+    /// rasm of block -> [(sura, vers, word, block), ...]
+    blocks: HashMap<String, Vec<[usize; 4]>>,
+    /// rasm of word, without block separators -> [(sura, vers, word), ...]
+    words: HashMap<String, Vec<[usize; 3]>>,
+}
+
+impl RasmIndex {
+
+    /// Index all blocks and words of the mushaf. Words with no archigraphemes (۞ ۩) are left out.
+    pub fn build(conv: &Converter, quran: &Mushaf) -> RasmIndex {
+
+        // rasm of each unique block, computed only once
+        let rasm: Vec<String> = quran.tok.iter().map(|(_, pal)| conv.strip_paleo(pal).into_owned()).collect();
+
+        let mut index = RasmIndex::default();
+
+        for ((sura, vers, word), blocks) in quran.words() {
+            let mut word_rasm = String::new();
+            for (iblock, &itok) in blocks.iter().enumerate() {
+                let block_rasm = &rasm[itok];
+                if block_rasm.is_empty() {
+                    continue;
+                }
+                index.blocks.entry(block_rasm.clone()).or_default().push([sura, vers, word, iblock+1]);
+                word_rasm.push_str(block_rasm);
+            }
+            if !word_rasm.is_empty() {
+                index.words.entry(word_rasm).or_default().push([sura, vers, word]);
+            }
+        }
+
+        index
+    }
+
+    /// Positions of block with Latin rasm, eg. KBBKG.
+    pub fn block(&self, rasm: &str) -> &[[usize; 4]] {
+        self.blocks.get(rasm).map_or(&[], |ind| ind.as_slice())
+    }
+
+    /// Positions of word with Latin rasm. Spaces between blocks are ignored, so "A LLH" and "ALLH" are the same.
+    pub fn word(&self, rasm: &str) -> &[[usize; 3]] {
+        let rasm: String = rasm.split_whitespace().collect();
+        self.words.get(&rasm).map_or(&[], |ind| ind.as_slice())
+    }
+
+    /// Iterate over all indexed block rasms and their positions.
+    pub fn blocks(&self) -> impl Iterator<Item = (&str, &[[usize; 4]])> {
+        self.blocks.iter().map(|(rasm, ind)| (rasm.as_str(), ind.as_slice()))
+    }
+
+    /// Iterate over all indexed word rasms and their positions.
+    pub fn words(&self) -> impl Iterator<Item = (&str, &[[usize; 3]])> {
+        self.words.iter().map(|(rasm, ind)| (rasm.as_str(), ind.as_slice()))
+    }
+}
@@ -0,0 +1,942 @@
+/*
+ *    inventory.rs
+ *
+ * inventory of Arabic characters and mappings to archigraphemes
+ *
+ * MIT License
+ * 
+ * Copyright (c) 2022 Alicia González Martínez
+ * 
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ * 
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ * 
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+
+/// Archigraphemes in Latin script.
+pub const ARCHIGRAPHEMES: &str = "QNYJABGRDTCSFEWHOMLK";
+
+/// Archigraphemes that only appear at the end of a word: final qaf, nun and ya.
+pub const FINAL_ONLY: &str = "QNY";
+
+#[derive(Debug)]
+pub struct Arachars {
+    pub Q: &'static str,
+    pub N: &'static str,
+    pub Y: &'static str,
+    pub J: &'static str,
+    pub A: &'static str,
+    pub B: &'static str,
+    pub G: &'static str,
+    pub R: &'static str,
+    pub D: &'static str,
+    pub T: &'static str,
+    pub C: &'static str,
+    pub S: &'static str,
+    pub F: &'static str,
+    pub E: &'static str,
+    pub W: &'static str,
+    pub H: &'static str,
+    pub O: &'static str,
+    pub M: &'static str,
+    pub L: &'static str,
+    pub K: &'static str,
+    pub diac: &'static str,
+    pub clusters: HashMap<&'static str, &'static str>,
+}
+
+impl Arachars {
+
+    /// All letters of the inventory, i.e. every character that belongs to an archigrapheme.
+    pub fn letters(&self) -> String {
+        [self.Q, self.N, self.Y, self.J, self.A, self.B, self.G, self.R, self.D, self.T,
+         self.C, self.S, self.F, self.E, self.W, self.H, self.O, self.M, self.L, self.K].concat()
+    }
+}
+
+#[derive(Debug)]
+pub struct Mappings {
+    pub mapping_end: HashMap<String, String>,
+    pub mapping_gen: HashMap<String, String>,
+    pub mapping_ara: HashMap<&'static str, &'static str>,
+    pub mapping_pal: HashMap<&'static str, &'static str>,
+}
+
+pub fn load_rasm_mappings(arachars: &Arachars) -> Mappings {
+
+    Mappings {
+
+        mapping_end: c!{c.to_string() => "Q".to_string(), for c in arachars.Q.chars()}.into_iter()
+              .chain(c!{c.to_string() => "N".to_string(), for c in arachars.N.chars()})
+              .chain(c!{c.to_string() => "Y".to_string(), for c in arachars.Y.chars()}).collect(),
+
+        mapping_gen: c!{c.to_string() => "B".to_string(), for c in [arachars.N, arachars.Y, arachars.B].concat().chars()}.into_iter()
+              .chain(c!{c.to_string() => "G".to_string(), for c in arachars.G.chars()})
+              .chain(c!{c.to_string() => "T".to_string(), for c in arachars.T.chars()})
+              .chain(c!{c.to_string() => "C".to_string(), for c in arachars.C.chars()})
+              .chain(c!{c.to_string() => "S".to_string(), for c in arachars.S.chars()})
+              .chain(c!{c.to_string() => "F".to_string(), for c in [arachars.Q, arachars.F].concat().chars()})
+              .chain(c!{c.to_string() => "E".to_string(), for c in arachars.E.chars()})
+              .chain(c!{c.to_string() => "H".to_string(), for c in arachars.H.chars()})
+              .chain(c!{c.to_string() => "O".to_string(), for c in arachars.O.chars()})
+              .chain(c!{c.to_string() => "M".to_string(), for c in arachars.M.chars()})
+              .chain(c!{c.to_string() => "L".to_string(), for c in arachars.L.chars()})
+              .chain(c!{c.to_string() => "K".to_string(), for c in arachars.K.chars()})
+              .chain(c!{c.to_string() => "A".to_string(), for c in arachars.A.chars()})
+              .chain(c!{c.to_string() => "R".to_string(), for c in arachars.R.chars()})
+              .chain(c!{c.to_string() => "D".to_string(), for c in arachars.D.chars()})
+              .chain(c!{c.to_string() => "W".to_string(), for c in arachars.W.chars()})
+              .chain(c!{c.to_string() => "J".to_string(), for c in arachars.J.chars()}).collect(),
+
+        mapping_ara: hashmap! {
+                    "Q" => "ٯ" ,
+                    "N" => "ں" ,
+                    "Y" => "ی" ,
+                    "J" => "ے" ,
+                    "B" => "ٮ" ,
+                    "G" => "ح" ,
+                    "T" => "ط" ,
+                    "C" => "ص" ,
+                    "S" => "س" ,
+                    "F" => "ڡ" ,
+                    "E" => "ع" ,
+                    "H" => "ه" ,
+                    "O" => "ھ" ,
+                    "M" => "م" ,
+                    "L" => "ل" ,
+                    "K" => "ك" ,
+                    "A" => "ا" ,
+                    "R" => "ر" ,
+                    "D" => "د" ,
+                    "W" => "و" ,
+        },
+
+        mapping_pal: hashmap! {
+                    "ء" => "ʔ",
+                    "أ" => "اˀ",
+                    "ﺃ" => "اˀ",
+                    "ﺄ" => "اˀ",
+                    "ٲ" => "اˀ",
+                    "ٵ" => "اˀ",
+                    "إ" => "اɂ",
+                    "ﺇ" => "اɂ",
+                    "ﺈ" => "اɂ",
+                    "ٳ" => "اɂ",
+                    "ٱ" => "اᵟ",
+                    "ﭐ" => "اᵟ",
+                    "ﭑ" => "اᵟ",
+                    "آ" => "ا˜",
+                    "آ" => "ا˜",
+                    "ﺁ" => "ا˜",
+                    "ﺂ" => "ا˜",
+                    "ﴼ" => "اᵃⁿ",
+                    "ݳ" => "ا۲",  // Urdu/Persian encoding of Numerals
+                    "ݴ" => "ا۳",
+                    "ࢥ" => "ٯ₁",  // U+08a5 ARABIC LETTER QAF WITH DOT BELOW
+                    "ڧ" => "ٯ¹",
+                    "ق" => "ٯ²",
+                    "ڨ" => "ٯ³",
+                    "ﻕ" => "ٯ²",
+                    "ﻖ" => "ٯ²",
+                    "ن" => "ں¹",
+                    "ڹ" => "ں₁",
+                    "ݧ" => "ں₂",
+                    "ڽ" => "ں³",
+                    "ﻥ" => "ں¹",
+                    "ﻦ" => "ں¹",
+                    "ڻ" => "ںᵀ",
+                    "ڼ" => "ںₒ",
+                    "ݨ" => "ںᵀ¹",  // we encode from up to bottom
+                    "ݩ" => "ںᵛ¹",
+                    "ي" => "ی₂",  // U+064a Arabic ya (normalise to Persian ya)
+                    //'ی' : 'ی' ,   # U+06cc Farsi ya
+                    "ى" => "ی",  // U+0649 Alif maqsura (normalise to Persian ya)
+                    "ې" => "ی₂",
+                    "ۑ" => "ی₃",
+                    "ؾ" => "ی²",
+                    "ؿ" => "ی³",
+                    "ﻲ" => "ی₂",
+                    "ﮰ" => "یˀ",
+                    "ﮱ" => "یˀ",
+                    "ﺉ" => "یˀ",
+                    "ﺊ" => "یˀ",
+                    "ئ" => "یˀ",
+                    "ۓ" => "یˀ",
+                    "ݷ" => "ی۴",
+                    "ؠ" => "یₒ",
+                    "ؽ" => "یᶺ",
+                    "ێ" => "یᵛ",
+                    "ݵ" => "ی۲",
+                    "ݶ" => "ی۳",
+                    "ݺ" => "ی۲",
+                    "ݻ" => "ی۳",
+                    "ب" => "ٮ₁",
+                    "ٻ" => "ٮ₂",
+                    "ݐ" => "ٮ₃",
+                    "پ" => "ٮ₃",
+                    "ڀ" => "ٮ₄",
+                    "ݒ" => "ٮ₃",
+                    "ݔ" => "ٮ¹₂",
+                    "ت" => "ٮ²",  // we don't keep a distinction between this and the next
+                    "ٺ" => "ٮ²",  // (there are other cases like this one)
+                    "ݓ" => "ٮ²₃",
+                    "ث" => "ٮ³",
+                    "ٽ" => "ٮ³",
+                    "ٿ" => "ٮ⁴",
+                    "ݑ" => "ٮ³₁",
+                    "ﻧ" => "ٮ¹",
+                    "ﻨ" => "ٮ¹",
+                    "ﯾ" => "ٮ₂",
+                    "ﯿ" => "ٮ₂",
+                    "ﻳ" => "ٮ₂",
+                    "ﻴ" => "ٮ₂",
+                    "ﺋ" => "ٮˀ",
+                    "ﺌ" => "ٮˀ",
+                    "ﺏ" => "ٮ₁",
+                    "ﺐ" => "ٮ₁",
+                    "ﺑ" => "ٮ₁",
+                    "ﺒ" => "ٮ₁",
+                    "ﭖ" => "ٮ₃",
+                    "ﭗ" => "ٮ₃",
+                    "ﭘ" => "ٮ₃",
+                    "ﭙ" => "ٮ₃",
+                    "ﺕ" => "ٮ²",
+                    "ﺖ" => "ٮ²",
+                    "ﺗ" => "ٮ²",
+                    "ﺘ" => "ٮ²",
+                    "ﺙ" => "ٮ³",
+                    "ﺚ" => "ٮ³",
+                    "ﺛ" => "ٮ³",
+                    "ﺜ" => "ٮ³",
+                    "ࢬ" => "ٮ₂",
+                    "ݕ" => "ٮ‸",
+                    "ࢠ" => "ٮᵥ",
+                    "ٹ" => "ٮᵀ",
+                    "ݖ" => "ٮᵛ",
+                    "ټ" => "ٮₒ",
+                    "خ" => "ح¹",
+                    "ج" => "ح₁",
+                    "چ" => "ح₃",
+                    "ڃ" => "ح₂",
+                    "ڄ" => "ح₂",
+                    "چ" => "ح₃",
+                    "ڇ" => "ح₄",
+                    "ݘ" => "ح₃",
+                    "ڿ" => "ح¹₃",
+                    "ڂ" => "ح²",
+                    "ݗ" => "ح²",
+                    "ࢢ" => "ح₂",  // U+08a2 ARABIC LETTER JEEM WITH TWO DOTS ABOVE
+                    "څ" => "ح³",
+                    "ﺝ" => "ح₁",
+                    "ﺞ" => "ح₁",
+                    "ﺟ" => "ح₁",
+                    "ﺠ" => "ح₁",
+                    "ﺥ" => "ح¹",
+                    "ﺦ" => "ح¹",
+                    "ﺧ" => "ح¹",
+                    "ﺨ" => "ح¹",
+                    "ﭺ" => "ح₃",
+                    "ﭻ" => "ح₃",
+                    "ﭼ" => "ح₃",
+                    "ﭽ" => "ح₃",
+                    "ځ" => "حˀ",
+                    "ݮ" => "حт",
+                    "ݼ" => "ح۴",
+                    "ݯ" => "حт₂",
+                    "ݲ" => "حᵀ",
+                    "ز" => "ر¹",
+                    "ڔ" => "ر₁",
+                    "ݬ" => "رˀ",
+                    "ږ" => "ر¹₁",
+                    "ڗ" => "ر²",
+                    "ݫ" => "ر²",
+                    "ژ" => "ر³",
+                    "ڙ" => "ر⁴",
+                    "ﺯ" => "ر¹",
+                    "ﺰ" => "ر¹",
+                    "ﮊ" => "ر³",
+                    "ﮋ" => "ر³",
+                    "ړ" => "رₒ",
+                    "ݛ" => "ر₋",
+                    "ࢪ" => "ر",  // U+08aa ARABIC LETTER REH WITH LOOP
+                    "ڕ" => "رᵥ",
+                    "ڑ" => "رᵀ",
+                    "ڒ" => "رᵛ",
+                    "ۯ" => "رᶺ",
+                    "ݱ" => "رᵀ²",
+                    "ذ" => "د¹",
+                    "ڊ" => "د₁",
+                    "ڍ" => "د₂",
+                    "ڌ" => "د²",
+                    "ڎ" => "د³",
+                    "ڏ" => "د³",
+                    "ڐ" => "د⁴",
+                    "ﺫ" => "د¹",
+                    "ﺬ" => "د¹",
+                    "ڈ" => "دᵀ",
+                    "ډ" => "دₒ",
+                    "ݚ" => "د‸",
+                    "ۮ" => "دᶺ",
+                    "ڋ" => "دᵀ₁",
+                    "ݙ" => "دᵀ₂",
+                    "ظ" => "ط¹",
+                    "ࢣ" => "ط²",  // U+08a3 ARABIC LETTER TAH WITH TWO DOTS ABOVE
+                    "ڟ" => "ط³",
+                    "ﻅ" => "ط¹",
+                    "ﻆ" => "ط¹",
+                    "ﻇ" => "ط¹",
+                    "ﻈ" => "ط¹",
+                    "ض" => "ص¹",
+                    "ڝ" => "ص₂",
+                    "ۻ" => "ص¹₁",
+                    "ڞ" => "ص³",
+                    "ﺽ" => "ص¹",
+                    "ﺾ" => "ص¹",
+                    "ﺿ" => "ص¹",
+                    "ﻀ" => "ص¹",
+                    "ش" => "س³",
+                    "ڛ" => "س₃",
+                    "ښ" => "س¹₁",
+                    "ݭ" => "س²",
+                    "ݜ" => "س³",
+                    "ۺ" => "س³₁",
+                    "ڜ" => "س³₃",
+                    "ﺵ" => "س³",
+                    "ﺶ" => "س³",
+                    "ﺷ" => "س³",
+                    "ﺸ" => "س³",
+                    "ݽ" => "س۴",
+                    "ݾ" => "سᶺ",
+                    "ݰ" => "سᵀ²",
+                    "ف" => "ڡ¹",
+                    "ﻑ" => "ڡ¹",
+                    "ﻒ" => "ڡ¹",
+                    "ڢ" => "ڡ₁",
+                    "ݠ" => "ڡ₂",
+                    "ڥ" => "ڡ₃",
+                    "ݡ" => "ڡ₃",
+                    "ڣ" => "ڡ¹₁",
+                    "ڤ" => "ڡ³",
+                    "ڦ" => "ڡ⁴",
+                    "ࢤ" => "ڡ³₁",  // U+08a4 ARABIC LETTER FEH WITH DOT BELOW AND THREE DOTS ABOVE
+                    "ﻓ" => "ڡ¹",
+                    "ﻔ" => "ڡ¹",
+                    "ﻗ" => "ڡ²",
+                    "ﻘ" => "ڡ²",
+                    "غ" => "ع¹",
+                    "ۼ" => "ع¹₁",
+                    "ݝ" => "ع²",
+                    "ݟ" => "ع²",
+                    "ڠ" => "ع³",
+                    "ݞ" => "ع³",
+                    "ﻍ" => "ع¹",
+                    "ﻎ" => "ع¹",
+                    "ﻏ" => "ع¹",
+                    "ﻐ" => "ع¹",
+                    "ؤ" => "وˀ",
+                    "ۏ" => "و¹",
+                    "ۊ" => "و²",
+                    "ۋ" => "و³",
+                    "ﺅ" => "وˀ",
+                    "ﺆ" => "وˀ",
+                    "ۄ" => "وₒ",  //FIXME
+                    "ۅ" => "و₋",  //FIXME
+                    "ࢫ" => "وₒ",  // U+08ab ARABIC LETTER WAW WITH DOT WITHIN #FIXME
+                    "ۆ" => "وᵛ",
+                    "ۇ" => "وᵠ",  //FIXME
+                    "ۈ" => "و।",  //FIXME
+                    "ۉ" => "وᶺ",
+                    "ݸ" => "و۲",
+                    "ݹ" => "و۳",
+                    "ۀ" => "هˀ",
+                    "ۂ" => "هˀ",
+                    "ة" => "ه²",
+                    "ۃ" => "ه²",
+                    "ﮤ" => "هˀ",
+                    "ﮥ" => "هˀ",
+                    "ﺓ" => "ه²",
+                    "ﺔ" => "ه²",
+                    "ۿ" => "هᶺ",
+                    "ݦ" => "م₁",
+                    "ݥ" => "م¹",
+                    "ࢧ" => "م³",  // U+08a7 ARABIC LETTER MEEM WITH THREE DOTS ABOVE
+                    "ڸ" => "ل₃",
+                    "ڶ" => "ل¹",
+                    "ڷ" => "ل³",
+                    "ݪ" => "ل₋",
+                    "ڵ" => "لᵛ",
+                    "ؼ" => "ك₃",
+                    "ڮ" => "ك₃",
+                    "ݤ" => "ك₃",
+                    "ݢ" => "ك¹",
+                    "ػ" => "ك²",
+                    "ݿ" => "ك²ˀ",
+                    "ڭ" => "ك³",
+                    "ݣ" => "ك³",
+                    "ګ" => "ك",  // FIXME
+                    "ڰ" => "كᐟ",  //FIXME
+                    "ڲ" => "كᐟ₂",
+                    "ڳ" => "كᐟ₂",
+                    "ڱ" => "ك²ᐟ",
+                    "ڴ" => "ك³ᐟ",
+                    "گ" => "كᐟ",
+                    "ﮓ" => "كᐟ",
+                    "ﮔ" => "كᐟ",
+                    "ﮕ" => "كᐟ",
+                    "َ" => "ᵃ",  // fatha
+                    "ً" => "ᵃⁿ",  // fathatan
+                    "ࣰ" => "ᵃᵃ",  // open fathatan
+                    "ُ" => "ᵘ",  // damma
+                    "ٌ" => "ᵘⁿ",  // dammatan
+                    "ࣱ" => "ᵘᵘ",  // open dammatan
+                    "ِ" => "ᵢ",  // kasra
+                    "ٍ" => "ᵢₙ",  // kasratan
+                    "ࣲ" => "ᵢᵢ",  // open kasratan
+                    "ّ" => "ᵚ",  // sadda
+                    "ۡ" => "ᵒ",  // quranic sukun
+                    "ْ" => "ᵒ",  // normal sukun
+                    "ٓ" => "˜",  // madda
+                    "ۨ" => "ᴺ",  // minuature nun above
+                    "ٰ" => "ᴬ",  // dagger alif
+                    "ۜ" => "ˢ",  // miniature sin above
+                    "ۣ" => "ₛ",  // miniature sin below
+                    "ۢ" => "ᵐ",  // minuature mim above   #FIXME Mᴹᴍ Yyʏ
+                    "ۭ" => "ₘ",  // minuature mim below
+                    "ۥ" => "ʷ",  // minuature waw
+                    "ۦ" => "ʸ",  // miniature ya
+                    "ۧ" => "ʸ",  // minuature ya above
+                    "۟" => "°",  // U+06df ARABIC SMALL HIGH ROUNDED ZERO - small circle | U+00B0 DEGREE SIGN
+                    //   the letter is additional and should not be pronounced either in connection nor pause
+                    "۠" => "⁰",  // U+06e0 ARABIC SMALL HIGH UPRIGHT RECTANGULAR ZERO - oval sign
+                    //   above an alif followed by a vowel letter, indicates that it is additional in consecutive reading
+                    //   but should be pronounced in pause
+                    "۫" => "⌃",  // U+06eb ARABIC EMPTY CENTRE HIGH STOP | U+2303 (alt-08963)  UP ARROWHEAD ; hapax تَأۡمَ۫نَّا
+                    "۪" => "⌄",  // U+06ea ARABIC EMPTY CENTRE LOW STOP | U+2304 DOWN ARROWHEAD ; hapax مَجۡر۪ىٰهَا
+                    "۬" => "•",  // U+06ec ARABIC ROUNDED HIGH STOP WITH FILLED CENTRE | U+2022 BULLET ; hapax ءَا۬عۡجَمِىࣱّ
+                    "ٔ" => "ˀ",  // hamza above
+                    "ٕ" => "ɂ",  // hamza below
+                    //"ـٔ " => "ˀ",  // U+0640 "ـ" tatweel is ALWAYS followed by hamza above, eg. ٱلۡأَفۡـِٔدَةِ 104:7:4,601:49,821:8:4
+                    // pausal marks
+                    "ۖ" => "⒮",  // U+06d6 ARABIC SMALL HIGH LIGATURE SAD WITH LAM WITH ALEF MAKSURA
+                    "ۗ" => "⒬",  // U+06d7 ARABIC SMALL HIGH LIGATURE QAF WITH LAM WITH ALEF MAKSURA
+                    "ۘ" => "⒨",  // U+06d8 ARABIC SMALL HIGH MEEM INITIAL FORM
+                    "ۙ" => "⒧",  // U+06d9 ARABIC SMALL HIGH LAM ALEF
+                    "ۚ" => "⒥",  // U+06da ARABIC SMALL HIGH JEEM
+                    "ۛ" => "∴",  // U+06db ARABIC SMALL HIGH THREE DOTS
+        },
+    }
+}
+
+pub fn load_arabic_inventory() -> Arachars {
+
+    Arachars {
+
+        Q: "ٯࢥڧقڨﻕﻖ",
+        N: "ںنڻڼڹݧݨݩڽﻥﻦ",
+        Y: "ىیۍݷيېۑؠئؽێݵݶࢨࢩؾؿﻯﻰﯼﯽﻲﺉﺊ",
+        J: "ےۓݺݻﮮﮯﮰﮱ",  // YEH BARREE - I think it's always final
+        A: "ٱأإآاٳٲݳݴٵﺃﺄﺇﺈﺁﺂﺍﺎﭐﭑﴼ",
+        B: "ࢬٮبݕࢠٻݐپڀݒٹݖݔتٺټݓثٽٿݑﻧﻨﯾﯿﻳﻴﺋﺌﺏﺐﺑﺒﭖﭗﭘﭙﺕﺖﺗﺘﺙﺚﺛﺜ",
+        G: "خحجچݮݼڃڄچڇݘݯځݲڿڂݗࢢڅﺝﺞﺟﺠﺡﺢﺣﺤﺥﺦﺧﺨﭺﭻﭼﭽ",
+        R: "رزړݛࢪڔڕڑڒۯݬږڗݫژڙݱﺭﺮﺯﺰﮊﮋ",
+        D: "دذڈډڊݚڍڈۮڋݙڌڎڏڐﺩﺪﺫﺬ",
+        T: "طظࢣڟﻁﻂﻃﻄﻅﻆﻇﻈ",
+        C: "صضڝۻڞﺹﺺﺻﺼﺽﺾﺿﻀ",
+        S: "سشڛݽݾښݭݜݰۺڜﺱﺲﺳﺴﺵﺶﺷﺸ",
+        F: "فﻑﻒڡڢݠڥݡڣڤڦࢤﻓﻔﻗﻘ",
+        E: "عغۼݝݟڠݞﻉﻊﻋﻌﻍﻎﻏﻐ",
+        W: "وۄۅࢫؤۆۇۈۉۏݸݹۊۋﻭﻮﺅﺆ",
+        H: "هہەۀۂۿةۃﮤﮥﺓﺔﮦﮧﮨﮩﻪﻫﻬ",
+        O: "ھﮪﮫﮬﮭ", // HEH DOACHASHMEE
+        M: "مݦݥࢧﻡﻢﻣﻤ",
+        L: "لݪࢦڸڵڶڷﻝﻞﻟﻠ",
+        K: "كکڪګگڰڲڳؼڮݤݢػڱݿڭڴݣﻙﻚﻛﻜﮎﮏﮐﮑﮒﮓﮔﮕ",
+
+        
+        diac: "ءـًٌٍَُِّٰٕۣٓۤٔۜ۟۠ۡۢۥۦࣰࣱࣲْ۪ۭۧۨ۫۬ﱞﳲﳳﳴﹱﹷﹹﹻﹽﹿ‍",
+        
+        clusters: hashmap! {
+                    "ﯪ" => "ئا",
+                    "ﯫ" => "ئا",
+                    "ﯬ" => "ئە",
+                    "ﯭ" => "ئە",
+                    "ﯮ" => "ئو",
+                    "ﯯ" => "ئو",
+                    "ﯰ" => "ئۇ",
+                    "ﯱ" => "ئۇ",
+                    "ﯲ" => "ئۆ",
+                    "ﯳ" => "ئۆ",
+                    "ﯴ" => "ئۈ",
+                    "ﯵ" => "ئۈ",
+                    "ﯶ" => "ئې",
+                    "ﯷ" => "ئې",
+                    "ﯸ" => "ئې",
+                    "ﯹ" => "ئى",
+                    "ﯺ" => "ئى",
+                    "ﯻ" => "ئى",
+                    "ﰃ" => "ئى",
+                    "ﱨ" => "ئى",
+                    "ﰀ" => "ئج",
+                    "ﲗ" => "ئج",
+                    "ﰁ" => "ئح",
+                    "ﲘ" => "ئح",
+                    "ﰂ" => "ئم",
+                    "ﱦ" => "ئم",
+                    "ﲚ" => "ئم",
+                    "ﳟ" => "ئم",
+                    "ﰄ" => "ئي",
+                    "ﱩ" => "ئي",
+                    "ﰅ" => "بج",
+                    "ﲜ" => "بج",
+                    "ﰆ" => "بح",
+                    "ﲝ" => "بح",
+                    "ﰇ" => "بخ",
+                    "ﲞ" => "بخ",
+                    "ﰈ" => "بم",
+                    "ﱬ" => "بم",
+                    "ﲟ" => "بم",
+                    "ﳡ" => "بم",
+                    "ﰉ" => "بى",
+                    "ﱮ" => "بى",
+                    "ﰊ" => "بي",
+                    "ﱯ" => "بي",
+                    "ﰋ" => "تج",
+                    "ﲡ" => "تج",
+                    "ﰌ" => "تح",
+                    "ﲢ" => "تح",
+                    "ﰍ" => "تخ",
+                    "ﲣ" => "تخ",
+                    "ﰎ" => "تم",
+                    "ﱲ" => "تم",
+                    "ﲤ" => "تم",
+                    "ﳣ" => "تم",
+                    "ﰏ" => "تى",
+                    "ﱴ" => "تى",
+                    "ﰐ" => "تي",
+                    "ﱵ" => "تي",
+                    "ﰑ" => "ثج",
+                    "ﰒ" => "ثم",
+                    "ﱸ" => "ثم",
+                    "ﲦ" => "ثم",
+                    "ﳥ" => "ثم",
+                    "ﰓ" => "ثى",
+                    "ﱺ" => "ثى",
+                    "ﰔ" => "ثي",
+                    "ﱻ" => "ثي",
+                    "ﰕ" => "جح",
+                    "ﲧ" => "جح",
+                    "ﰖ" => "جم",
+                    "ﲨ" => "جم",
+                    "ﰗ" => "حج",
+                    "ﲩ" => "حج",
+                    "ﰘ" => "حم",
+                    "ﲪ" => "حم",
+                    "ﰙ" => "خج",
+                    "ﲫ" => "خج",
+                    "ﰚ" => "خح",
+                    "ﰛ" => "خم",
+                    "ﲬ" => "خم",
+                    "ﰜ" => "سج",
+                    "ﲭ" => "سج",
+                    "ﴴ" => "سج",
+                    "ﰝ" => "سح",
+                    "ﲮ" => "سح",
+                    "ﴵ" => "سح",
+                    "ﰞ" => "سخ",
+                    "ﲯ" => "سخ",
+                    "ﴶ" => "سخ",
+                    "ﰟ" => "سم",
+                    "ﲰ" => "سم",
+                    "ﳧ" => "سم",
+                    "ﰠ" => "صح",
+                    "ﲱ" => "صح",
+                    "ﰡ" => "صم",
+                    "ﲳ" => "صم",
+                    "ﰢ" => "ضج",
+                    "ﲴ" => "ضج",
+                    "ﰣ" => "ضح",
+                    "ﲵ" => "ضح",
+                    "ﰤ" => "ضخ",
+                    "ﲶ" => "ضخ",
+                    "ﰥ" => "ضم",
+                    "ﲷ" => "ضم",
+                    "ﰦ" => "طح",
+                    "ﲸ" => "طح",
+                    "ﰧ" => "طم",
+                    "ﴳ" => "طم",
+                    "ﴺ" => "طم",
+                    "ﰨ" => "ظم",
+                    "ﲹ" => "ظم",
+                    "ﴻ" => "ظم",
+                    "ﰩ" => "عج",
+                    "ﲺ" => "عج",
+                    "ﰪ" => "عم",
+                    "ﲻ" => "عم",
+                    "ﰫ" => "غج",
+                    "ﲼ" => "غج",
+                    "ﰬ" => "غم",
+                    "ﲽ" => "غم",
+                    "ﰭ" => "فج",
+                    "ﲾ" => "فج",
+                    "ﰮ" => "فح",
+                    "ﲿ" => "فح",
+                    "ﰯ" => "فخ",
+                    "ﳀ" => "فخ",
+                    "ﰰ" => "فم",
+                    "ﳁ" => "فم",
+                    "ﰱ" => "فى",
+                    "ﱼ" => "فى",
+                    "ﰲ" => "في",
+                    "ﱽ" => "في",
+                    "ﰳ" => "قح",
+                    "ﳂ" => "قح",
+                    "ﰴ" => "قم",
+                    "ﳃ" => "قم",
+                    "ﰵ" => "قى",
+                    "ﱾ" => "قى",
+                    "ﰶ" => "قي",
+                    "ﱿ" => "قي",
+                    "ﰷ" => "كا",
+                    "ﲀ" => "كا",
+                    "ﰸ" => "كج",
+                    "ﳄ" => "كج",
+                    "ﰹ" => "كح",
+                    "ﳅ" => "كح",
+                    "ﰺ" => "كخ",
+                    "ﳆ" => "كخ",
+                    "ﰻ" => "كل",
+                    "ﲁ" => "كل",
+                    "ﳇ" => "كل",
+                    "ﳫ" => "كل",
+                    "ﰼ" => "كم",
+                    "ﲂ" => "كم",
+                    "ﳈ" => "كم",
+                    "ﳬ" => "كم",
+                    "ﰽ" => "كى",
+                    "ﲃ" => "كى",
+                    "ﰾ" => "كي",
+                    "ﲄ" => "كي",
+                    "ﰿ" => "لج",
+                    "ﳉ" => "لج",
+                    "ﱀ" => "لح",
+                    "ﳊ" => "لح",
+                    "ﱁ" => "لخ",
+                    "ﳋ" => "لخ",
+                    "ﱂ" => "لم",
+                    "ﲅ" => "لم",
+                    "ﳌ" => "لم",
+                    "ﳭ" => "لم",
+                    "ﱃ" => "لى",
+                    "ﲆ" => "لى",
+                    "ﱄ" => "لي",
+                    "ﲇ" => "لي",
+                    "ﱅ" => "مج",
+                    "ﳎ" => "مج",
+                    "ﱆ" => "مح",
+                    "ﳏ" => "مح",
+                    "ﱇ" => "مخ",
+                    "ﳐ" => "مخ",
+                    "ﱈ" => "مم",
+                    "ﲉ" => "مم",
+                    "ﳑ" => "مم",
+                    "ﱉ" => "مى",
+                    "ﱊ" => "مي",
+                    "ﱋ" => "نج",
+                    "ﳒ" => "نج",
+                    "ﱌ" => "نح",
+                    "ﳓ" => "نح",
+                    "ﱍ" => "نخ",
+                    "ﳔ" => "نخ",
+                    "ﱎ" => "نم",
+                    "ﲌ" => "نم",
+                    "ﳕ" => "نم",
+                    "ﳮ" => "نم",
+                    "ﱏ" => "نى",
+                    "ﲎ" => "نى",
+                    "ﱐ" => "ني",
+                    "ﲏ" => "ني",
+                    "ﱑ" => "هج",
+                    "ﳗ" => "هج",
+                    "ﱒ" => "هم",
+                    "ﳘ" => "هم",
+                    "ﱓ" => "هى",
+                    "ﱔ" => "هي",
+                    "ﱕ" => "يج",
+                    "ﳚ" => "يج",
+                    "ﱖ" => "يح",
+                    "ﳛ" => "يح",
+                    "ﱗ" => "يخ",
+                    "ﳜ" => "يخ",
+                    "ﱘ" => "يم",
+                    "ﲓ" => "يم",
+                    "ﳝ" => "يم",
+                    "ﳰ" => "يم",
+                    "ﱙ" => "يى",
+                    "ﲕ" => "يى",
+                    "ﱚ" => "يي",
+                    "ﲖ" => "يي",
+                    "ﱛ" => "ذ",
+                    "ﱜ" => "ر",
+                    "ﱝ" => "ى",
+                    "ﲐ" => "ى",
+                    "ﱤ" => "ئر",
+                    "ﱥ" => "ئز",
+                    "ﱧ" => "ئن",
+                    "ﱪ" => "بر",
+                    "ﱫ" => "بز",
+                    "ﱭ" => "بن",
+                    "ﱰ" => "تر",
+                    "ﱱ" => "تز",
+                    "ﱳ" => "تن",
+                    "ﱶ" => "ثر",
+                    "ﱷ" => "ثز",
+                    "ﱹ" => "ثن",
+                    "ﲈ" => "ما",
+                    "ﲊ" => "نر",
+                    "ﲋ" => "نز",
+                    "ﲍ" => "نن",
+                    "ﲑ" => "ير",
+                    "ﲒ" => "يز",
+                    "ﲔ" => "ين",
+                    "ﲙ" => "ئخ",
+                    "ﲛ" => "ئه",
+                    "ﳠ" => "ئه",
+                    "ﲠ" => "به",
+                    "ﳢ" => "به",
+                    "ﲥ" => "ته",
+                    "ﳤ" => "ته",
+                    "ﲲ" => "صخ",
+                    "ﳍ" => "له",
+                    "ﳖ" => "نه",
+                    "ﳯ" => "نه",
+                    "ﳙ" => "ه",
+                    "ﳞ" => "يه",
+                    "ﳱ" => "يه",
+                    "ﳦ" => "ثه",
+                    "ﳨ" => "سه",
+                    "ﴱ" => "سه",
+                    "ﳩ" => "شم",
+                    "ﴌ" => "شم",
+                    "ﴨ" => "شم",
+                    "ﴰ" => "شم",
+                    "ﳪ" => "شه",
+                    "ﴲ" => "شه",
+                    "ﳵ" => "طى",
+                    "ﴑ" => "طى",
+                    "ﳶ" => "طي",
+                    "ﴒ" => "طي",
+                    "ﳷ" => "عى",
+                    "ﴓ" => "عى",
+                    "ﳸ" => "عي",
+                    "ﴔ" => "عي",
+                    "ﳹ" => "غى",
+                    "ﴕ" => "غى",
+                    "ﳺ" => "غي",
+                    "ﴖ" => "غي",
+                    "ﳻ" => "سى",
+                    "ﴗ" => "سى",
+                    "ﳼ" => "سي",
+                    "ﴘ" => "سي",
+                    "ﳽ" => "شى",
+                    "ﴙ" => "شى",
+                    "ﳾ" => "شي",
+                    "ﴚ" => "شي",
+                    "ﳿ" => "حى",
+                    "ﴛ" => "حى",
+                    "ﴀ" => "حي",
+                    "ﴜ" => "حي",
+                    "ﴁ" => "جى",
+                    "ﴝ" => "جى",
+                    "ﴂ" => "جي",
+                    "ﴞ" => "جي",
+                    "ﴃ" => "خى",
+                    "ﴟ" => "خى",
+                    "ﴄ" => "خي",
+                    "ﴠ" => "خي",
+                    "ﴅ" => "صى",
+                    "ﴡ" => "صى",
+                    "ﴆ" => "صي",
+                    "ﴢ" => "صي",
+                    "ﴇ" => "ضى",
+                    "ﴣ" => "ضى",
+                    "ﴈ" => "ضي",
+                    "ﴤ" => "ضي",
+                    "ﴉ" => "شج",
+                    "ﴥ" => "شج",
+                    "ﴭ" => "شج",
+                    "ﴷ" => "شج",
+                    "ﴊ" => "شح",
+                    "ﴦ" => "شح",
+                    "ﴮ" => "شح",
+                    "ﴸ" => "شح",
+                    "ﴋ" => "شخ",
+                    "ﴧ" => "شخ",
+                    "ﴯ" => "شخ",
+                    "ﴹ" => "شخ",
+                    "ﴍ" => "شر",
+                    "ﴩ" => "شر",
+                    "ﴎ" => "سر",
+                    "ﴪ" => "سر",
+                    "ﴏ" => "صر",
+                    "ﴫ" => "صر",
+                    "ﴐ" => "ضر",
+                    "ﴬ" => "ضر",
+                    "ﵐ" => "تجم",
+                    "ﵑ" => "تحج",
+                    "ﵒ" => "تحج",
+                    "ﵓ" => "تحم",
+                    "ﵔ" => "تخم",
+                    "ﵕ" => "تمج",
+                    "ﵖ" => "تمح",
+                    "ﵗ" => "تمخ",
+                    "ﵘ" =>   "جمح",
+                    "ﵙ" => "جمح",
+                    "ﵚ" => "حمي",
+                    "ﵛ" => "حمى",
+                    "ﵜ" => "سحج",
+                    "ﵝ" => "سجح",
+                    "ﵞ" => "سجى",
+                    "ﵟ" => "سمح",
+                    "ﵠ" => "سمح",
+                    "ﵡ" => "سمج",
+                    "ﵢ" => "سمم",
+                    "ﵣ" => "سمم",
+                    "ﵤ" => "صحح",
+                    "ﵥ" => "صحح",
+                    "ﵦ" => "صمم",
+                    "ﷅ" => "صمم",
+                    "ﵧ" => "شحم",
+                    "ﵨ" => "شحم",
+                    "ﵩ" => "شجي",
+                    "ﵪ" => "شمخ",
+                    "ﵫ" => "شمخ",
+                    "ﵬ" => "شمم",
+                    "ﵭ" => "شمم",
+                    "ﵮ" => "ضحى",
+                    "ﵯ" => "ضخم",
+                    "ﵰ" => "ضخم",
+                    "ﵱ" => "طمح",
+                    "ﵲ" => "طمح",
+                    "ﵳ" => "طمم",
+                    "ﵴ" => "طمي",
+                    "ﵵ" => "عجم",
+                    "ﷄ" => "عجم",
+                    "ﵶ" => "عمم",
+                    "ﵷ" => "عمم",
+                    "ﵸ" => "عمى",
+                    "ﵹ" => "غمم",
+                    "ﵺ" => "غمي",
+                    "ﵻ" => "غمى",
+                    "ﵼ" => "فخم",
+                    "ﵽ" => "فخم",
+                    "ﵾ" => "قمح",
+                    "ﶴ" => "قمح",
+                    "ﵿ" => "قمم",
+                    "ﶀ" => "لحم",
+                    "ﶵ" => "لحم",
+                    "ﶁ" => "لحي",
+                    "ﶂ" => "لحى",
+                    "ﶃ" => "لجج",
+                    "ﶄ" => "لجج",
+                    "ﶅ" => "لخم",
+                    "ﶆ" => "لخم",
+                    "ﶇ" => "لمح",
+                    "ﶈ" => "لمح",
+                    "ﶉ" => "محج",
+                    "ﶊ" => "محم",
+                    "ﶋ" => "محي",
+                    "ﶌ" => "مجح",
+                    "ﶍ" => "مجم",
+                    "ﶎ" => "مخج",
+                    "ﶏ" => "مخم",
+                    "ﶒ" => "مجخ",
+                    "ﶓ" => "همج",
+                    "ﶔ" => "همم",
+                    "ﶕ" => "نحم",
+                    "ﶖ" => "نحى",
+                    "ﶗ" => "نجم",
+                    "ﶘ" => "نجم",
+                    "ﶙ" => "نجى",
+                    "ﶚ" => "نمي",
+                    "ﶛ" => "نمى",
+                    "ﶜ" => "يمم",
+                    "ﶝ" => "يمم",
+                    "ﶞ" => "بخي",
+                    "ﶟ" => "تجي",
+                    "ﶠ" => "تجى",
+                    "ﶡ" => "تخي",
+                    "ﶢ" => "تخى",
+                    "ﶣ" => "تمي",
+                    "ﶤ" => "تمى",
+                    "ﶥ" => "جمي",
+                    "ﶦ" => "جحى",
+                    "ﶧ" => "جمى",
+                    "ﶨ" => "سخى",
+                    "ﶩ" => "صحي",
+                    "ﶪ" => "شحي",
+                    "ﶫ" => "ضحي",
+                    "ﶬ" => "لجي",
+                    "ﶭ" => "لمي",
+                    "ﶮ" => "يحي",
+                    "ﶯ" => "يجي",
+                    "ﶰ" => "يمي",
+                    "ﶱ" => "ممي",
+                    "ﶲ" => "قمي",
+                    "ﶳ" => "نحي",
+                    "ﶶ" => "عمي",
+                    "ﶷ" => "كمي",
+                    "ﶸ" => "نجح",
+                    "ﶽ" => "نجح",
+                    "ﶹ" => "مخي",
+                    "ﶺ" => "لجم",
+                    "ﶼ" => "لجم",
+                    "ﶻ" => "كمم",
+                    "ﷃ" => "كمم",
+                    "ﶾ" => "جحي",
+                    "ﶿ" => "حجي",
+                    "ﷀ" => "مجي",
+                    "ﷁ" => "فمي",
+                    "ﷂ" => "بحي",
+                    "ﷆ" => "سخي",
+                    "ﷇ" => "نجي",
+                    "ﻵ" => "لآ",
+                    "ﻶ" => "لآ",
+                    "ﻷ" => "لأ",
+                    "ﻸ" => "لأ",
+                    "ﻹ" => "لإ",
+                    "ﻺ" => "لإ",
+                    "ﻻ" => "لا",
+                    "ﻼ" => "لا",
+                    "ﷺ" => "صلى الله عليه وسلم",
+                    "﷽" => "بسم الله الرحمن الرحيم",
+                    "ﷲ" => "الله",
+                    "ﷳ" => "أكبر",
+                    "ﷴ" => "محمد",
+                    "ﷶ" => "رسول",
+                    "ﷷ" => "عليه",
+                    "ﷸ" => "وسلم",
+                    "ﷹ" => "صلى",
+                    "﷼" => "ریال",
+                    "ﷻ" => "جل جلاله",
+                    "ﷱ" => "قلے",
+                    "ﷰ" => "صلے",
+                    "ﷵ" => "صلعم",
+        }
+    }
+}
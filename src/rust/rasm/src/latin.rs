@@ -0,0 +1,156 @@
+/*
+ *    latin.rs
+ *
+ * parsing of rasm written in Latin archigraphemes, with or without spaces between letterblocks
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::error::Error;
+use std::fmt;
+
+use crate::convert::{Converter, Reduced};
+use crate::inventory::{ARCHIGRAPHEMES, FINAL_ONLY};
+
+/// Archigraphemes that close a letterblock.
+const CLOSING: &str = "ARDW";
+
+/// Word in Latin archigraphemes that cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatinError {
+    pub word: String,
+    /// character where the error was found, starting in 1
+    pub position: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for LatinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid Latin rasm \"{}\" at position {}: {}", self.word, self.position, self.reason)
+    }
+}
+
+impl Error for LatinError {}
+
+/// Parse a word in Latin archigraphemes, eg. ALLH or "A LLH", into its letterblocks and render them
+/// in Arabic script. Spaces are optional, but when present they may only follow a letter that closes
+/// a block (A, R, D or W), and the final-only letters Q, N and Y must be the last letter of the word.
+/// The original field of the result keeps the word as written.
+pub fn parse_word(conv: &Converter, word: &str) -> Result<Reduced, LatinError> {
+
+    let error = |position: usize, reason: String| LatinError { word: word.to_string(), position, reason };
+
+    let mut blocks: Vec<String> = vec![String::new()];
+    let mut prev: Option<char> = None;
+
+    for (i, c) in word.chars().enumerate() {
+        match c {
+            ' ' => match prev {
+                None => return Err(error(i+1, "space before the first letter".to_string())),
+                Some(' ') => return Err(error(i+1, "more than one space between letterblocks".to_string())),
+                Some(p) if !CLOSING.contains(p) =>
+                    return Err(error(i+1, format!("space after {}, which does not close a letterblock", p))),
+                Some(_) => {},
+            },
+            _ if ARCHIGRAPHEMES.contains(c) => {
+                if let Some(p) = prev.filter(|&p| FINAL_ONLY.contains(p)) {
+                    return Err(error(i, format!("{} can only appear at the end of a word", p)));
+                }
+                if prev.is_some_and(|p| CLOSING.contains(p) || p == ' ') {
+                    blocks.push(String::new());
+                }
+                blocks.last_mut().unwrap().push(c);
+            }
+            _ => return Err(error(i+1, format!("unexpected character '{}', expected one of {} or space",
+                                                c, ARCHIGRAPHEMES))),
+        }
+        prev = Some(c);
+    }
+
+    match prev {
+        None => return Err(error(0, "empty word".to_string())),
+        Some(' ') => return Err(error(word.chars().count(), "space after the last letter".to_string())),
+        Some(_) => {},
+    }
+
+    let lat = blocks.join(" ");
+    let ara = conv.to_arabic(&lat).into_owned();
+
+    Ok(Reduced { ori: word.to_string(), lat, ara, pal: String::new() })
+}
+
+/// Split a line into words in Latin archigraphemes. If the line contains tabs, each tab-separated field
+/// is a word, whose letterblocks may be separated by spaces, eg. "FAL\tA LLH"; otherwise the words are
+/// separated by whitespace and their letterblocks must be written together, eg. "FAL ALLH".
+pub fn split_words(line: &str) -> Vec<&str> {
+    if line.contains('\t') {
+        line.split('\t').map(str::trim).filter(|w| !w.is_empty()).collect()
+    } else {
+        line.split_whitespace().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn parse(word: &str) -> Result<(String, String), (usize, String)> {
+        parse_word(&Converter::new(), word)
+            .map(|red| (red.lat, red.ara))
+            .map_err(|err| (err.position, err.reason))
+    }
+
+    #[test]
+    fn valid_words() {
+        assert_eq!(parse("FAL"), Ok(("FA L".to_string(), "ڡا ل".to_string())));
+        assert_eq!(parse("ALLH"), Ok(("A LLH".to_string(), "ا لله".to_string())));
+        assert_eq!(parse("A LLH"), Ok(("A LLH".to_string(), "ا لله".to_string())));
+        assert_eq!(parse("A LR GMN"), Ok(("A LR GMN".to_string(), "ا لر حمں".to_string())));
+        assert_eq!(parse("FY"), Ok(("FY".to_string(), "ڡی".to_string())));
+    }
+
+    #[test]
+    fn final_only_letters() {
+        assert_eq!(parse("QAL").unwrap_err().0, 1);
+        assert_eq!(parse("BNB").unwrap_err(), (2, "N can only appear at the end of a word".to_string()));
+        assert_eq!(parse("A LYM").unwrap_err().0, 4);
+        assert!(parse("A LGMN").is_ok());
+    }
+
+    #[test]
+    fn bad_spaces() {
+        assert_eq!(parse("B L").unwrap_err(), (2, "space after B, which does not close a letterblock".to_string()));
+        assert_eq!(parse("A  LLH").unwrap_err(), (3, "more than one space between letterblocks".to_string()));
+        assert_eq!(parse(" ALLH").unwrap_err().0, 1);
+        assert_eq!(parse("ALLH ").unwrap_err().0, 5);
+        assert_eq!(parse("ALxH").unwrap_err().0, 3);
+    }
+
+    #[test]
+    fn split_words_by_tabs() {
+        assert_eq!(split_words("FAL ALLH"), vec!["FAL", "ALLH"]);
+        assert_eq!(split_words("FA L\tA LLH\t"), vec!["FA L", "A LLH"]);
+    }
+}
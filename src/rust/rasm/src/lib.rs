@@ -0,0 +1,65 @@
+/*
+ *    lib.rs
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+#![allow(non_snake_case)]
+
+#[macro_use] extern crate maplit;
+#[macro_use] extern crate serde_derive;
+extern crate regex;
+extern crate serde;
+extern crate serde_json;
+
+#[macro_use(c)]
+extern crate cute;
+
+pub mod inventory;
+pub mod align;
+pub mod collate;
+pub mod concordance;
+pub mod convert;
+pub mod density;
+pub mod dotting;
+pub mod encoding;
+pub mod mushaf;
+pub mod homograph;
+pub mod index;
+pub mod latin;
+pub mod minhash;
+pub mod ngram;
+pub mod paleo;
+pub mod positions;
+pub mod quran;
+pub mod quote;
+pub mod range;
+pub mod restore;
+pub mod rhyme;
+pub mod search;
+pub mod segment;
+pub mod sources;
+pub mod translit;
+pub mod unpaleo;
+pub mod verify;
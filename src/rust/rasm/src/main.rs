@@ -33,613 +33,1630 @@
  *   execute: $ ./main.rs
  *
  * usage:
- *   $ RUSTFLAGS="$RUSTFLAGS -A dead_code" cargo build && ./target/debug/rasm --infile <(echo -e "بِسۡمِ ﷲ ٱلرَّحۡمَٰنِ\nٱلرَّحِيمِ\nٱلرَّحۡمَٰنِ ٱلaaرَّحِيمِ")
+ *   $ cargo build && ./target/debug/rasm --infile <(echo -e "بِسۡمِ ﷲ ٱلرَّحۡمَٰنِ\nٱلرَّحِيمِ\nٱلرَّحۡمَٰنِ ٱلaaرَّحِيمِ")
  *   OR
- *   $ cargo run -- --infile <(echo -e "بِسۡمِ ﷲ ٱلرَّحۡمَٰنِ\nٱلرَّحِيمِ\nٱلرَّحۡمَٰنِ ٱلaaرَّحِيمِ")
+ *   $ cargo run -- --infile <(echo -e "بِسۡمِ ﷲ ٱلرَّحۡمَٰنِ\nٱلرَّحِيمِ\nٱلرَّحۡمَٰنِ ٱلaaرَّحِيمِ")
+ *
+ *   render Latin rasm in Arabic script and count its unique letterblocks:
+ *   $ echo "FAL ALLH" | cargo run -- --latin --uniq
+ *
+ *   reduce text in Buckwalter transliteration, writing the tokens back in Buckwalter:
+ *   $ echo "bisomi {ll~ahi" | cargo run -- --input-translit buckwalter --output-translit buckwalter
+ *
+ *   convert a legacy file in Windows-1256, replacing what cannot be decoded:
+ *   $ cargo run -- --infile folio.txt --encoding cp1256 --lossy
+ *   the encoding options go before the subcommand and apply to all of them:
+ *   $ cargo run -- --encoding iso-8859-6 density folio.txt
+ *
+ *   retrieve quranic text by index, sura name or division:
+ *   $ cargo run -- --quran "al-Baqara:255,juz 30" --source tanzil-uthmani
+ *
+ *   find where a rasm word or block occurs in the Quran:
+ *   $ cargo run -- index "A LLH" KBR
+ *   $ cargo run -- index LLH --blocks
+ *
+ *   search the Quran by rasm pattern or Arabic word:
+ *   $ cargo run -- search "B?LM*" --quran "juz 1"
+ *
+ *   list the dotted readings that share a skeleton:
+ *   $ cargo run -- homographs --quran all --pattern BBB
+ *
+ *   generate dotting candidates of a skeleton attested in the Quran:
+ *   $ cargo run -- candidates KBBKG ڪٮٮكح --quran all
+ *
+ *   restore the dots of undotted text with a model trained on the Quran:
+ *   $ echo "ٮسم الله الرحمں الرحىم" | cargo run -- restore
+ *
+ *   segment a stream of letterblocks into words:
+ *   $ echo "BSM A LLH A LR GMN A LR GBM" | cargo run -- segment
+ *
+ *   align the transcription of a folio with the Quran:
+ *   $ cargo run --release -- align folio.txt --source tanzil-uthmani
+ *
+ *   collate two witnesses of a text:
+ *   $ cargo run -- collate witness1.txt witness2.txt --apparatus
+ *
+ *   detect quranic quotations in a text:
+ *   $ cargo run --release -- quotes chronicle.txt --min 5
+ *
+ *   find clusters of near-duplicate documents:
+ *   $ cargo run --release -- dedup corpus/ --threshold 0.8
+ *
+ *   count rasm word bigrams of a corpus:
+ *   $ cargo run --release -- ngrams corpus.txt --unit word -n 2
+ *
+ *   show the occurrences of a letterblock in context:
+ *   $ cargo run -- kwic LHKM --blocks --quran all --context 4
+ *
+ *   count archigraphemes by position in the letterblock for each sura:
+ *   $ cargo run -- positions --quran all --json
+ *
+ *   measure how densely each line of a manuscript transcription is pointed:
+ *   $ cargo run -- density folio.txt --level line
+ *
+ *   compare the verse-final rhymes of the two tanzil sources:
+ *   $ cargo run -- rhyme --compare -n 2
+ *
+ *   list the orthographic differences between two editions:
+ *   $ cargo run -- compare-sources tanzil-simple tanzil-uthmani --summary
+ *
+ *   write Arabic from paleo-orthographic notation, one word per token:
+ *   $ echo "B₁ᵢSᵒMᵢ Aᵟ LLᵚᵃHᵢ" | cargo run -- from-paleo
+ *
+ *   build quran data structures:
+ *   $ cargo run --release -- build quran-simple.txt ../../../rasm_arch_data/mushaf_simple.json
+ *   $ cargo run --release -- build --format decotype mushaf.json ../../../rasm_arch_data/mushaf_dt.json
  *
  ************************************************************************************************************/
 
-#![allow(non_snake_case)]
-
-#[macro_use] extern crate maplit;
 extern crate argparse;
-extern crate regex;
-
-#[macro_use(c)]
-extern crate cute;
+extern crate rasm;
+extern crate serde_json;
 
 use std::io::prelude::*;
-use std::io::{BufReader, Result};
-use std::fs::File;
+use std::io::{stdin, stdout, stderr, BufReader, BufWriter, ErrorKind, Result};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 
+use std::borrow::Cow;
 use std::process;
-use std::collections::HashMap;
-use argparse::{ArgumentParser, StoreTrue, Store};
-use regex::{Captures, Regex};
-
-#[derive(Debug)]
-struct Arachars {
-    Q: &'static str,
-    N: &'static str,
-    Y: &'static str,
-    A: &'static str,
-    B: &'static str,
-    G: &'static str,
-    R: &'static str,
-    D: &'static str,
-    T: &'static str,
-    C: &'static str,
-    S: &'static str,
-    F: &'static str,
-    E: &'static str,
-    W: &'static str,
-    H: &'static str,
-    M: &'static str,
-    L: &'static str,
-    K: &'static str,
-    diac: &'static str,
-    clusters: HashMap<&'static str, &'static str>,
-}
-
-#[derive(Debug)]
-struct Mappings {
-    mapping_end: HashMap<String, String>,
-    mapping_gen: HashMap<String, String>,
-    mapping_ara: HashMap<&'static str, &'static str>,
-}
-
-fn load_rasm_mappings(arachars: &Arachars) -> Mappings {
-
-    Mappings {
-
-        mapping_end: c!{c.to_string() => "Q".to_string(), for c in arachars.Q.chars()}.into_iter()
-              .chain(c!{c.to_string() => "N".to_string(), for c in arachars.N.chars()}).into_iter()
-              .chain(c!{c.to_string() => "Y".to_string(), for c in arachars.Y.chars()}).collect(),
-
-        mapping_gen: c!{c.to_string() => "B".to_string(), for c in (arachars.N.chars().as_str().to_string().clone() +
-                                                                  &(arachars.Y.chars().as_str().to_string()) + 
-                                                                  &(arachars.B.chars().as_str().to_string())).chars()}.into_iter()
-              .chain(c!{c.to_string() => "G".to_string(), for c in arachars.G.chars()}).into_iter()
-              .chain(c!{c.to_string() => "T".to_string(), for c in arachars.T.chars()}).into_iter()
-              .chain(c!{c.to_string() => "C".to_string(), for c in arachars.C.chars()}).into_iter()
-              .chain(c!{c.to_string() => "S".to_string(), for c in arachars.S.chars()}).into_iter()
-              .chain(c!{c.to_string() => "F".to_string(), for c in arachars.F.chars()}).into_iter()
-              .chain(c!{c.to_string() => "E".to_string(), for c in arachars.E.chars()}).into_iter()
-              .chain(c!{c.to_string() => "H".to_string(), for c in arachars.H.chars()}).into_iter()
-              .chain(c!{c.to_string() => "M".to_string(), for c in arachars.M.chars()}).into_iter()
-              .chain(c!{c.to_string() => "L".to_string(), for c in arachars.L.chars()}).into_iter()
-              .chain(c!{c.to_string() => "K".to_string(), for c in arachars.K.chars()}).into_iter()
-              .chain(c!{c.to_string() => "A ".to_string(), for c in arachars.A.chars()}).into_iter()
-              .chain(c!{c.to_string() => "R ".to_string(), for c in arachars.R.chars()}).into_iter()
-              .chain(c!{c.to_string() => "D ".to_string(), for c in arachars.D.chars()}).into_iter()
-              .chain(c!{c.to_string() => "W ".to_string(), for c in arachars.T.chars()}).collect(),
-
-        mapping_ara: hashmap! {
-                    "Q" => "ٯ" ,
-                    "N" => "ں" ,
-                    "Y" => "ی" ,
-                    "B" => "ٮ" ,
-                    "G" => "ح" ,
-                    "T" => "ط" ,
-                    "C" => "ص" ,
-                    "S" => "س" ,
-                    "F" => "ڡ" ,
-                    "E" => "ع" ,
-                    "H" => "ه" ,
-                    "M" => "م" ,
-                    "L" => "ل" ,
-                    "K" => "ک" ,
-                    "A" => "ا" ,
-                    "R" => "ر" ,
-                    "D" => "د" ,
-                    "W" => "و" ,
-        },
-    }
-}
-
-
-fn load_arabic_inventory() -> Arachars {
-
-    Arachars {
-
-        Q: "ٯࢥڧقڨﻕﻖفﻑﻒ",
-        N: "ںنڻڼڹݧݨݩڽﻥﻦ",
-        Y: "ىیۍݷيېۑؠئؽێݵݶࢨࢩؾؿےۓݺݻﻯﻰﮮﮯیﯼﯽﻲﮰﮱﺉﺊ",
-        A: "ٱأإآاٳٲݳݴٵﺃﺄﺇﺈﺁﺂﺍﺎﭐﭑﴼ",
-        B: "ࢬٮبݕࢠٻݐپڀݒٹݖݔتٺټݓثٽٿݑﻧﻨﯾﯿﻳﻴۦﺋﺌﺏﺐﺑﺒﭖﭗﭘﭙﺕﺖﺗﺘﺙﺚﺛﺜٮ",
-        G: "خحجچݮݼڃڄچڇݘݯځݲڿڂݗࢢڅﺝﺞﺟﺠﺡﺢﺣﺤﺥﺦﺧﺨﭺﭻﭼﭽ",
-        R: "رزړݛࢪڔڕڑڒۯݬږڗݫژڙݱﺭﺮﺯﺰﮊﮋ",
-        D: "دذڈډڊݚڍڈۮڋݙڌڎڏڐﺩﺪﺫﺬ",
-        T: "طظࢣڟﻁﻂﻃﻄﻅﻆﻇﻈ",
-        C: "صضڝۻڞﺹﺺﺻﺼﺽﺾﺿﻀ",
-        S: "سشڛݽݾښݭݜݰۺڜﺱﺲﺳﺴﺵﺶﺷﺸ",
-        F: "ڡڢݠڥݡڣڤڦࢤﻓﻔﻗﻘ",
-        E: "عغۼݝݟڠݞﻉﻊﻋﻌﻍﻎﻏﻐ",
-        W: "وۄۅࢫؤۆۇۈۉۏݸݹۊۋﻭﻮﺅﺆ",
-        H: "هھہەۀۂۿةۃﮤﮥﺓﺔﮦﮧﮨﮩﻪﻫﻬﮪﮫﮬﮭ",
-        M: "مݦݥࢧﻡﻢﻣﻤ",
-        L: "لݪࢦڸڵڶڷﻝﻞﻟﻠ",
-        K: "كکڪګگڰڲڳؼڮݤڮݢػڱݿڭڴݣﻙﻚﻛﻜکﮎﮏﮐﮑﮒﮓﮔﮕ",
-        
-        diac: "ءـًٌٍَُِّٰٕۣٓۤٔۜ۟۠ۡۢۥۦࣰࣱࣲْ۪ۭۧۨ۫۬ﱞﳲﳳﳴﹱﹷﹹﹻﹽﹿ‍",
-        
-        clusters: hashmap! {
-                    "ﯪ" => "ئا",
-                    "ﯫ" => "ئا",
-                    "ﯬ" => "ئە",
-                    "ﯭ" => "ئە",
-                    "ﯮ" => "ئو",
-                    "ﯯ" => "ئو",
-                    "ﯰ" => "ئۇ",
-                    "ﯱ" => "ئۇ",
-                    "ﯲ" => "ئۆ",
-                    "ﯳ" => "ئۆ",
-                    "ﯴ" => "ئۈ",
-                    "ﯵ" => "ئۈ",
-                    "ﯶ" => "ئې",
-                    "ﯷ" => "ئې",
-                    "ﯸ" => "ئې",
-                    "ﯹ" => "ئى",
-                    "ﯺ" => "ئى",
-                    "ﯻ" => "ئى",
-                    "ﰃ" => "ئى",
-                    "ﱨ" => "ئى",
-                    "ﰀ" => "ئج",
-                    "ﲗ" => "ئج",
-                    "ﰁ" => "ئح",
-                    "ﲘ" => "ئح",
-                    "ﰂ" => "ئم",
-                    "ﱦ" => "ئم",
-                    "ﲚ" => "ئم",
-                    "ﳟ" => "ئم",
-                    "ﰄ" => "ئي",
-                    "ﱩ" => "ئي",
-                    "ﰅ" => "بج",
-                    "ﲜ" => "بج",
-                    "ﰆ" => "بح",
-                    "ﲝ" => "بح",
-                    "ﰇ" => "بخ",
-                    "ﲞ" => "بخ",
-                    "ﰈ" => "بم",
-                    "ﱬ" => "بم",
-                    "ﲟ" => "بم",
-                    "ﳡ" => "بم",
-                    "ﰉ" => "بى",
-                    "ﱮ" => "بى",
-                    "ﰊ" => "بي",
-                    "ﱯ" => "بي",
-                    "ﰋ" => "تج",
-                    "ﲡ" => "تج",
-                    "ﰌ" => "تح",
-                    "ﲢ" => "تح",
-                    "ﰍ" => "تخ",
-                    "ﲣ" => "تخ",
-                    "ﰎ" => "تم",
-                    "ﱲ" => "تم",
-                    "ﲤ" => "تم",
-                    "ﳣ" => "تم",
-                    "ﰏ" => "تى",
-                    "ﱴ" => "تى",
-                    "ﰐ" => "تي",
-                    "ﱵ" => "تي",
-                    "ﰑ" => "ثج",
-                    "ﰒ" => "ثم",
-                    "ﱸ" => "ثم",
-                    "ﲦ" => "ثم",
-                    "ﳥ" => "ثم",
-                    "ﰓ" => "ثى",
-                    "ﱺ" => "ثى",
-                    "ﰔ" => "ثي",
-                    "ﱻ" => "ثي",
-                    "ﰕ" => "جح",
-                    "ﲧ" => "جح",
-                    "ﰖ" => "جم",
-                    "ﲨ" => "جم",
-                    "ﰗ" => "حج",
-                    "ﲩ" => "حج",
-                    "ﰘ" => "حم",
-                    "ﲪ" => "حم",
-                    "ﰙ" => "خج",
-                    "ﲫ" => "خج",
-                    "ﰚ" => "خح",
-                    "ﰛ" => "خم",
-                    "ﲬ" => "خم",
-                    "ﰜ" => "سج",
-                    "ﲭ" => "سج",
-                    "ﴴ" => "سج",
-                    "ﰝ" => "سح",
-                    "ﲮ" => "سح",
-                    "ﴵ" => "سح",
-                    "ﰞ" => "سخ",
-                    "ﲯ" => "سخ",
-                    "ﴶ" => "سخ",
-                    "ﰟ" => "سم",
-                    "ﲰ" => "سم",
-                    "ﳧ" => "سم",
-                    "ﰠ" => "صح",
-                    "ﲱ" => "صح",
-                    "ﰡ" => "صم",
-                    "ﲳ" => "صم",
-                    "ﰢ" => "ضج",
-                    "ﲴ" => "ضج",
-                    "ﰣ" => "ضح",
-                    "ﲵ" => "ضح",
-                    "ﰤ" => "ضخ",
-                    "ﲶ" => "ضخ",
-                    "ﰥ" => "ضم",
-                    "ﲷ" => "ضم",
-                    "ﰦ" => "طح",
-                    "ﲸ" => "طح",
-                    "ﰧ" => "طم",
-                    "ﴳ" => "طم",
-                    "ﴺ" => "طم",
-                    "ﰨ" => "ظم",
-                    "ﲹ" => "ظم",
-                    "ﴻ" => "ظم",
-                    "ﰩ" => "عج",
-                    "ﲺ" => "عج",
-                    "ﰪ" => "عم",
-                    "ﲻ" => "عم",
-                    "ﰫ" => "غج",
-                    "ﲼ" => "غج",
-                    "ﰬ" => "غم",
-                    "ﲽ" => "غم",
-                    "ﰭ" => "فج",
-                    "ﲾ" => "فج",
-                    "ﰮ" => "فح",
-                    "ﲿ" => "فح",
-                    "ﰯ" => "فخ",
-                    "ﳀ" => "فخ",
-                    "ﰰ" => "فم",
-                    "ﳁ" => "فم",
-                    "ﰱ" => "فى",
-                    "ﱼ" => "فى",
-                    "ﰲ" => "في",
-                    "ﱽ" => "في",
-                    "ﰳ" => "قح",
-                    "ﳂ" => "قح",
-                    "ﰴ" => "قم",
-                    "ﳃ" => "قم",
-                    "ﰵ" => "قى",
-                    "ﱾ" => "قى",
-                    "ﰶ" => "قي",
-                    "ﱿ" => "قي",
-                    "ﰷ" => "كا",
-                    "ﲀ" => "كا",
-                    "ﰸ" => "كج",
-                    "ﳄ" => "كج",
-                    "ﰹ" => "كح",
-                    "ﳅ" => "كح",
-                    "ﰺ" => "كخ",
-                    "ﳆ" => "كخ",
-                    "ﰻ" => "كل",
-                    "ﲁ" => "كل",
-                    "ﳇ" => "كل",
-                    "ﳫ" => "كل",
-                    "ﰼ" => "كم",
-                    "ﲂ" => "كم",
-                    "ﳈ" => "كم",
-                    "ﳬ" => "كم",
-                    "ﰽ" => "كى",
-                    "ﲃ" => "كى",
-                    "ﰾ" => "كي",
-                    "ﲄ" => "كي",
-                    "ﰿ" => "لج",
-                    "ﳉ" => "لج",
-                    "ﱀ" => "لح",
-                    "ﳊ" => "لح",
-                    "ﱁ" => "لخ",
-                    "ﳋ" => "لخ",
-                    "ﱂ" => "لم",
-                    "ﲅ" => "لم",
-                    "ﳌ" => "لم",
-                    "ﳭ" => "لم",
-                    "ﱃ" => "لى",
-                    "ﲆ" => "لى",
-                    "ﱄ" => "لي",
-                    "ﲇ" => "لي",
-                    "ﱅ" => "مج",
-                    "ﳎ" => "مج",
-                    "ﱆ" => "مح",
-                    "ﳏ" => "مح",
-                    "ﱇ" => "مخ",
-                    "ﳐ" => "مخ",
-                    "ﱈ" => "مم",
-                    "ﲉ" => "مم",
-                    "ﳑ" => "مم",
-                    "ﱉ" => "مى",
-                    "ﱊ" => "مي",
-                    "ﱋ" => "نج",
-                    "ﳒ" => "نج",
-                    "ﱌ" => "نح",
-                    "ﳓ" => "نح",
-                    "ﱍ" => "نخ",
-                    "ﳔ" => "نخ",
-                    "ﱎ" => "نم",
-                    "ﲌ" => "نم",
-                    "ﳕ" => "نم",
-                    "ﳮ" => "نم",
-                    "ﱏ" => "نى",
-                    "ﲎ" => "نى",
-                    "ﱐ" => "ني",
-                    "ﲏ" => "ني",
-                    "ﱑ" => "هج",
-                    "ﳗ" => "هج",
-                    "ﱒ" => "هم",
-                    "ﳘ" => "هم",
-                    "ﱓ" => "هى",
-                    "ﱔ" => "هي",
-                    "ﱕ" => "يج",
-                    "ﳚ" => "يج",
-                    "ﱖ" => "يح",
-                    "ﳛ" => "يح",
-                    "ﱗ" => "يخ",
-                    "ﳜ" => "يخ",
-                    "ﱘ" => "يم",
-                    "ﲓ" => "يم",
-                    "ﳝ" => "يم",
-                    "ﳰ" => "يم",
-                    "ﱙ" => "يى",
-                    "ﲕ" => "يى",
-                    "ﱚ" => "يي",
-                    "ﲖ" => "يي",
-                    "ﱛ" => "ذ",
-                    "ﱜ" => "ر",
-                    "ﱝ" => "ى",
-                    "ﲐ" => "ى",
-                    "ﱤ" => "ئر",
-                    "ﱥ" => "ئز",
-                    "ﱧ" => "ئن",
-                    "ﱪ" => "بر",
-                    "ﱫ" => "بز",
-                    "ﱭ" => "بن",
-                    "ﱰ" => "تر",
-                    "ﱱ" => "تز",
-                    "ﱳ" => "تن",
-                    "ﱶ" => "ثر",
-                    "ﱷ" => "ثز",
-                    "ﱹ" => "ثن",
-                    "ﲈ" => "ما",
-                    "ﲊ" => "نر",
-                    "ﲋ" => "نز",
-                    "ﲍ" => "نن",
-                    "ﲑ" => "ير",
-                    "ﲒ" => "يز",
-                    "ﲔ" => "ين",
-                    "ﲙ" => "ئخ",
-                    "ﲛ" => "ئه",
-                    "ﳠ" => "ئه",
-                    "ﲠ" => "به",
-                    "ﳢ" => "به",
-                    "ﲥ" => "ته",
-                    "ﳤ" => "ته",
-                    "ﲲ" => "صخ",
-                    "ﳍ" => "له",
-                    "ﳖ" => "نه",
-                    "ﳯ" => "نه",
-                    "ﳙ" => "ه",
-                    "ﳞ" => "يه",
-                    "ﳱ" => "يه",
-                    "ﳦ" => "ثه",
-                    "ﳨ" => "سه",
-                    "ﴱ" => "سه",
-                    "ﳩ" => "شم",
-                    "ﴌ" => "شم",
-                    "ﴨ" => "شم",
-                    "ﴰ" => "شم",
-                    "ﳪ" => "شه",
-                    "ﴲ" => "شه",
-                    "ﳵ" => "طى",
-                    "ﴑ" => "طى",
-                    "ﳶ" => "طي",
-                    "ﴒ" => "طي",
-                    "ﳷ" => "عى",
-                    "ﴓ" => "عى",
-                    "ﳸ" => "عي",
-                    "ﴔ" => "عي",
-                    "ﳹ" => "غى",
-                    "ﴕ" => "غى",
-                    "ﳺ" => "غي",
-                    "ﴖ" => "غي",
-                    "ﳻ" => "سى",
-                    "ﴗ" => "سى",
-                    "ﳼ" => "سي",
-                    "ﴘ" => "سي",
-                    "ﳽ" => "شى",
-                    "ﴙ" => "شى",
-                    "ﳾ" => "شي",
-                    "ﴚ" => "شي",
-                    "ﳿ" => "حى",
-                    "ﴛ" => "حى",
-                    "ﴀ" => "حي",
-                    "ﴜ" => "حي",
-                    "ﴁ" => "جى",
-                    "ﴝ" => "جى",
-                    "ﴂ" => "جي",
-                    "ﴞ" => "جي",
-                    "ﴃ" => "خى",
-                    "ﴟ" => "خى",
-                    "ﴄ" => "خي",
-                    "ﴠ" => "خي",
-                    "ﴅ" => "صى",
-                    "ﴡ" => "صى",
-                    "ﴆ" => "صي",
-                    "ﴢ" => "صي",
-                    "ﴇ" => "ضى",
-                    "ﴣ" => "ضى",
-                    "ﴈ" => "ضي",
-                    "ﴤ" => "ضي",
-                    "ﴉ" => "شج",
-                    "ﴥ" => "شج",
-                    "ﴭ" => "شج",
-                    "ﴷ" => "شج",
-                    "ﴊ" => "شح",
-                    "ﴦ" => "شح",
-                    "ﴮ" => "شح",
-                    "ﴸ" => "شح",
-                    "ﴋ" => "شخ",
-                    "ﴧ" => "شخ",
-                    "ﴯ" => "شخ",
-                    "ﴹ" => "شخ",
-                    "ﴍ" => "شر",
-                    "ﴩ" => "شر",
-                    "ﴎ" => "سر",
-                    "ﴪ" => "سر",
-                    "ﴏ" => "صر",
-                    "ﴫ" => "صر",
-                    "ﴐ" => "ضر",
-                    "ﴬ" => "ضر",
-                    "ﵐ" => "تجم",
-                    "ﵑ" => "تحج",
-                    "ﵒ" => "تحج",
-                    "ﵓ" => "تحم",
-                    "ﵔ" => "تخم",
-                    "ﵕ" => "تمج",
-                    "ﵖ" => "تمح",
-                    "ﵗ" => "تمخ",
-                    "ﵘ" =>   "جمح",
-                    "ﵙ" => "جمح",
-                    "ﵚ" => "حمي",
-                    "ﵛ" => "حمى",
-                    "ﵜ" => "سحج",
-                    "ﵝ" => "سجح",
-                    "ﵞ" => "سجى",
-                    "ﵟ" => "سمح",
-                    "ﵠ" => "سمح",
-                    "ﵡ" => "سمج",
-                    "ﵢ" => "سمم",
-                    "ﵣ" => "سمم",
-                    "ﵤ" => "صحح",
-                    "ﵥ" => "صحح",
-                    "ﵦ" => "صمم",
-                    "ﷅ" => "صمم",
-                    "ﵧ" => "شحم",
-                    "ﵨ" => "شحم",
-                    "ﵩ" => "شجي",
-                    "ﵪ" => "شمخ",
-                    "ﵫ" => "شمخ",
-                    "ﵬ" => "شمم",
-                    "ﵭ" => "شمم",
-                    "ﵮ" => "ضحى",
-                    "ﵯ" => "ضخم",
-                    "ﵰ" => "ضخم",
-                    "ﵱ" => "طمح",
-                    "ﵲ" => "طمح",
-                    "ﵳ" => "طمم",
-                    "ﵴ" => "طمي",
-                    "ﵵ" => "عجم",
-                    "ﷄ" => "عجم",
-                    "ﵶ" => "عمم",
-                    "ﵷ" => "عمم",
-                    "ﵸ" => "عمى",
-                    "ﵹ" => "غمم",
-                    "ﵺ" => "غمي",
-                    "ﵻ" => "غمى",
-                    "ﵼ" => "فخم",
-                    "ﵽ" => "فخم",
-                    "ﵾ" => "قمح",
-                    "ﶴ" => "قمح",
-                    "ﵿ" => "قمم",
-                    "ﶀ" => "لحم",
-                    "ﶵ" => "لحم",
-                    "ﶁ" => "لحي",
-                    "ﶂ" => "لحى",
-                    "ﶃ" => "لجج",
-                    "ﶄ" => "لجج",
-                    "ﶅ" => "لخم",
-                    "ﶆ" => "لخم",
-                    "ﶇ" => "لمح",
-                    "ﶈ" => "لمح",
-                    "ﶉ" => "محج",
-                    "ﶊ" => "محم",
-                    "ﶋ" => "محي",
-                    "ﶌ" => "مجح",
-                    "ﶍ" => "مجم",
-                    "ﶎ" => "مخج",
-                    "ﶏ" => "مخم",
-                    "ﶒ" => "مجخ",
-                    "ﶓ" => "همج",
-                    "ﶔ" => "همم",
-                    "ﶕ" => "نحم",
-                    "ﶖ" => "نحى",
-                    "ﶗ" => "نجم",
-                    "ﶘ" => "نجم",
-                    "ﶙ" => "نجى",
-                    "ﶚ" => "نمي",
-                    "ﶛ" => "نمى",
-                    "ﶜ" => "يمم",
-                    "ﶝ" => "يمم",
-                    "ﶞ" => "بخي",
-                    "ﶟ" => "تجي",
-                    "ﶠ" => "تجى",
-                    "ﶡ" => "تخي",
-                    "ﶢ" => "تخى",
-                    "ﶣ" => "تمي",
-                    "ﶤ" => "تمى",
-                    "ﶥ" => "جمي",
-                    "ﶦ" => "جحى",
-                    "ﶧ" => "جمى",
-                    "ﶨ" => "سخى",
-                    "ﶩ" => "صحي",
-                    "ﶪ" => "شحي",
-                    "ﶫ" => "ضحي",
-                    "ﶬ" => "لجي",
-                    "ﶭ" => "لمي",
-                    "ﶮ" => "يحي",
-                    "ﶯ" => "يجي",
-                    "ﶰ" => "يمي",
-                    "ﶱ" => "ممي",
-                    "ﶲ" => "قمي",
-                    "ﶳ" => "نحي",
-                    "ﶶ" => "عمي",
-                    "ﶷ" => "كمي",
-                    "ﶸ" => "نجح",
-                    "ﶽ" => "نجح",
-                    "ﶹ" => "مخي",
-                    "ﶺ" => "لجم",
-                    "ﶼ" => "لجم",
-                    "ﶻ" => "كمم",
-                    "ﷃ" => "كمم",
-                    "ﶾ" => "جحي",
-                    "ﶿ" => "حجي",
-                    "ﷀ" => "مجي",
-                    "ﷁ" => "فمي",
-                    "ﷂ" => "بحي",
-                    "ﷆ" => "سخي",
-                    "ﷇ" => "نجي",
-                    "ﻵ" => "لآ",
-                    "ﻶ" => "لآ",
-                    "ﻷ" => "لأ",
-                    "ﻸ" => "لأ",
-                    "ﻹ" => "لإ",
-                    "ﻺ" => "لإ",
-                    "ﻻ" => "لا",
-                    "ﻼ" => "لا",
-                    "ﷺ" => "صلى الله عليه وسلم",
-                    "﷽" => "بسم الله الرحمن الرحيم",
-                    "ﷲ" => "الله",
-                    "ﷳ" => "أكبر",
-                    "ﷴ" => "محمد",
-                    "ﷶ" => "رسول",
-                    "ﷷ" => "عليه",
-                    "ﷸ" => "وسلم",
-                    "ﷹ" => "صلى",
-                    "﷼" => "ریال",
-                    "ﷻ" => "جل جلاله",
-                    "ﷱ" => "قلے",
-                    "ﷰ" => "صلے",
-                    "ﷵ" => "صلعم",
-        }
-    }
-}
-
-fn main() -> Result<()> {
+use std::sync::OnceLock;
+use std::str::FromStr;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use argparse::{ArgumentParser, StoreTrue, Store, List};
+
+use rasm::align::{self, Edit};
+use rasm::collate::{self, Kind};
+use rasm::concordance;
+use rasm::convert::{Converter, Reduced};
+use rasm::density::{self, Density};
+use rasm::dotting::{self, DottingError, Lexicon};
+use rasm::encoding::{Decoder, Encoding};
+use rasm::homograph::Homographs;
+use rasm::index::RasmIndex;
+use rasm::latin;
+use rasm::minhash::{self, MinHasher};
+use rasm::mushaf::{self, Mushaf, Source};
+use rasm::ngram::{NgramCounter, Unit};
+use rasm::positions::{self, PositionStats};
+use rasm::quote::{self, QuoteIndex};
+use rasm::quran::format_index;
+use rasm::range::QuranRange;
+use rasm::restore::DottingModel;
+use rasm::rhyme;
+use rasm::search::{self, Pattern};
+use rasm::segment::{self, Vocabulary};
+use rasm::sources;
+use rasm::translit::{Scheme, Transliterator};
+use rasm::unpaleo::Unpaleo;
+use rasm::verify;
+
+enum Command {
+    Convert,
+    Build,
+    Verify,
+    Index,
+    Search,
+    Homographs,
+    Candidates,
+    Restore,
+    Segment,
+    Align,
+    Collate,
+    Quotes,
+    Dedup,
+    Ngrams,
+    Kwic,
+    Positions,
+    Density,
+    Rhyme,
+    CompareSources,
+    FromPaleo,
+}
+
+impl FromStr for Command {
+    type Err = ();
+    fn from_str(src: &str) -> std::result::Result<Command, ()> {
+        match src {
+            "build" => Ok(Command::Build),
+            "verify" => Ok(Command::Verify),
+            "index" => Ok(Command::Index),
+            "search" => Ok(Command::Search),
+            "homographs" => Ok(Command::Homographs),
+            "candidates" => Ok(Command::Candidates),
+            "restore" => Ok(Command::Restore),
+            "segment" => Ok(Command::Segment),
+            "align" => Ok(Command::Align),
+            "collate" => Ok(Command::Collate),
+            "quotes" => Ok(Command::Quotes),
+            "dedup" => Ok(Command::Dedup),
+            "ngrams" => Ok(Command::Ngrams),
+            "kwic" => Ok(Command::Kwic),
+            "positions" => Ok(Command::Positions),
+            "density" => Ok(Command::Density),
+            "rhyme" => Ok(Command::Rhyme),
+            "compare-sources" => Ok(Command::CompareSources),
+            "from-paleo" => Ok(Command::FromPaleo),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Encoding of the input given in the command line, shared by every command.
+struct InputEncoding {
+    /// None to detect it
+    encoding: Option<Encoding>,
+    lossy: bool,
+}
+
+static INPUT_ENCODING: OnceLock<InputEncoding> = OnceLock::new();
+
+/// Open file for reading, or stdin if path is empty or "-", decoding it into UTF-8 as it is read.
+fn open_input(path: &str) -> Result<Box<dyn BufRead>> {
+
+    let name = if path.is_empty() || path == "-" { "stdin" } else { path };
+    let fp: Box<dyn Read> = if name == "stdin" { Box::new(stdin()) } else { Box::new(File::open(path)?) };
+
+    let options = INPUT_ENCODING.get_or_init(|| InputEncoding { encoding: None, lossy: false });
+    let mut decoder = Decoder::new(fp, options.encoding)?;
+
+    if options.encoding.is_none() && decoder.encoding() != Encoding::Utf8 {
+        eprintln!("# encoding of {}: {}", name, decoder.encoding());
+    }
+    if options.lossy {
+        let name = name.to_string();
+        decoder = decoder.lossy(move |err| eprintln!("{}: {}", name, err));
+    }
+
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+/// Open file for writing, or stdout if path is empty or "-".
+fn open_output(path: &str) -> Result<Box<dyn Write>> {
+    if path.is_empty() || path == "-" {
+        Ok(Box::new(stdout()))
+    } else {
+        Ok(Box::new(File::create(path)?))
+    }
+}
+
+fn parse_subcommand_args(parser: ArgumentParser, args: Vec<String>) {
+    if let Err(x) = parser.parse(args, &mut stdout(), &mut stderr()) {
+        process::exit(x);
+    }
+}
+
+fn build_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut outfile = String::new();
+    let mut format = "tanzil".to_string();
+    let mut unstable = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("create quran data struct for rasm from tanzil (sura|verse|text) or decotype (json) quran");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "quranic text [DEFAULT stdin]");
+        parser.refer(&mut outfile)
+                    .add_argument("outfile", Store,
+                    "processed quran in json [DEFAULT stdout]");
+        parser.refer(&mut format)
+                    .add_option(&["-f", "--format"], Store,
+                    "format of the input: tanzil or decotype [DEFAULT tanzil]");
+        parser.refer(&mut unstable)
+                    .add_option(&["-u", "--unstable"], StoreTrue,
+                    "delete unstable alif");
+        parse_subcommand_args(parser, args);
+    }
+
+    let fp = open_input(&infile)?;
+
+    let words = match format.as_str() {
+        "tanzil" => mushaf::read_tanzil(fp)?,
+        "decotype" => mushaf::read_decotype(fp)?,
+        _ => {
+            eprintln!("format must be tanzil or decotype");
+            process::exit(2);
+        }
+    };
+
+    let conv = Converter::new();
+    let quran = mushaf::build(&conv, words, unstable);
+
+    quran.write_json(open_output(&outfile)?)
+}
+
+/// Load mushaf structure from file, or from the shipped source if path is empty.
+fn load_mushaf(path: &str, source: &str) -> Result<Mushaf> {
+    if !path.is_empty() {
+        return Mushaf::load(path);
+    }
+    match source.parse::<Source>() {
+        Ok(source) => Mushaf::load_source(source),
+        Err(()) => {
+            eprintln!("source must be tanzil-simple, tanzil-uthmani or decotype");
+            process::exit(2);
+        }
+    }
+}
+
+fn verify_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut unstable = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("check integrity of quran data struct and compare stored blocks with the current conversion");
+
+        parser.refer(&mut infile)
+                    .add_argument("mushaf", Store,
+                    "quran data struct in json [DEFAULT the one of --source]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut unstable)
+                    .add_option(&["-u", "--unstable"], StoreTrue,
+                    "the struct was built deleting unstable alif");
+        parse_subcommand_args(parser, args);
+    }
+
+    let quran = load_mushaf(&infile, &source)?;
+    let conv = Converter::new();
+
+    let problems = verify::verify(&conv, &quran, unstable);
+
+    let mut summary: HashMap<&str, usize> = HashMap::new();
+    for problem in &problems {
+        println!("{}", problem);
+        *summary.entry(problem.kind()).or_insert(0) += 1;
+    }
+
+    if problems.is_empty() {
+        println!("ok\t{} tok entries, {} words checked", quran.tok.len(), quran.words().count());
+        return Ok(());
+    }
+
+    let mut summary: Vec<_> = summary.into_iter().collect();
+    summary.sort();
+    println!("{} problems found: {}", problems.len(),
+        summary.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect::<Vec<_>>().join(", "));
+    process::exit(1);
+}
+
+fn index_command(args: Vec<String>) -> Result<()> {
+
+    let mut queries: Vec<String> = vec![];
+    let mut source = "tanzil-simple".to_string();
+    let mut blocks = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("find all the places of the Quran where a rasm word or block occurs. \
+                                Queries are Latin archigraphemes, eg. KBBKG, or Arabic script, which is reduced to archigraphemes");
+
+        parser.refer(&mut queries)
+                    .add_argument("rasm", List,
+                    "rasm words or blocks to look up").required();
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut blocks)
+                    .add_option(&["-b", "--blocks"], StoreTrue,
+                    "look up letterblocks instead of words");
+        parse_subcommand_args(parser, args);
+    }
+
+    let quran = load_mushaf("", &source)?;
+    let conv = Converter::new();
+    let index = RasmIndex::build(&conv, &quran);
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for query in &queries {
+
+        let rasm = if query.is_ascii() { query.clone() } else { conv.reduce(&conv.normalise(query)).lat };
+
+        let found: Vec<String> = if blocks {
+            index.block(&rasm).iter().map(|ind| format_index(ind)).collect()
+        } else {
+            index.word(&rasm).iter().map(|ind| format_index(ind)).collect()
+        };
+
+        writeln!(out, "{}\t{}\t{}", rasm, found.len(), found.join(" "))?;
+    }
+
+    out.flush()
+}
+
+/// Parse quranic range given as argument, exiting with a message if it is not valid.
+fn parse_range(range: &str) -> QuranRange {
+    match range.parse() {
+        Ok(range) => range,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+}
+
+fn search_command(args: Vec<String>) -> Result<()> {
+
+    let mut query = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut range = String::new();
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("search the Quran by rasm. The query is an Arabic word, which is reduced to archigraphemes, \
+                                or a pattern of Latin archigraphemes where ? is any archigrapheme, * any sequence of them \
+                                and [..] a character class, eg. B?LM* or A L[BN]*");
+
+        parser.refer(&mut query)
+                    .add_argument("query", Store,
+                    "Arabic word or Latin rasm pattern").required();
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "search only inside quranic range, eg. 2:1-2:100 or juz 30");
+        parse_subcommand_args(parser, args);
+    }
+
+    let range = if range.is_empty() { None } else { Some(parse_range(&range)) };
+
+    let conv = Converter::new();
+    let pattern = parse_pattern(&conv, &query);
+
+    let quran = load_mushaf("", &source)?;
+    let index = RasmIndex::build(&conv, &quran);
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for found in search::search(&conv, &quran, &index, &pattern, range.as_ref()) {
+        writeln!(out, "{}\t{}\t{}\t{}", format_index(&found.index), found.tok, found.lat, found.pal)?;
+    }
+
+    out.flush()
+}
+
+/// Compile rasm pattern given as argument, exiting with a message if it is not valid.
+fn parse_pattern(conv: &Converter, query: &str) -> Pattern {
+    match Pattern::new(conv, query) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+}
+
+/// Transliterator of the scheme given in the command line, if any.
+fn parse_translit(scheme: &str) -> Option<Transliterator> {
+    if scheme.is_empty() {
+        return None;
+    }
+    match scheme.parse::<Scheme>() {
+        Ok(scheme) => Some(Transliterator::new(scheme)),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+}
+
+fn homographs_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut range = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut vocalised = false;
+    let mut min = 2usize;
+    let mut pattern = String::new();
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("group the distinct tokens of a text or of the Quran by their rasm and print, for each skeleton, \
+                                its total frequency, number of realisations and realisations with their frequencies");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "Arabic text [DEFAULT stdin, unless --quran is given]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "use the quranic range instead of a text, eg. all or juz 30");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple] [only for --quran]");
+        parser.refer(&mut vocalised)
+                    .add_option(&["-v", "--vocalised"], StoreTrue,
+                    "keep vowels and other diacritics in the realisations, instead of only the dotted consonants");
+        parser.refer(&mut min)
+                    .add_option(&["-m", "--min"], Store,
+                    "print only skeletons with at least this number of distinct realisations [DEFAULT 2]");
+        parser.refer(&mut pattern)
+                    .add_option(&["-p", "--pattern"], Store,
+                    "print only skeletons matching Latin rasm pattern or Arabic word, eg. BBB or B?LM*");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+    let pattern = if pattern.is_empty() { None } else { Some(parse_pattern(&conv, &pattern)) };
+
+    let mut homographs = Homographs::new();
+    if range.is_empty() {
+        homographs.add_text(&conv, open_input(&infile)?, vocalised)?;
+    } else {
+        let range = parse_range(&range);
+        let quran = load_mushaf("", &source)?;
+        homographs.add_mushaf(&conv, &quran, Some(&range), vocalised);
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for group in homographs.groups() {
+        if group.realisations.len() < min || pattern.as_ref().is_some_and(|p| !p.is_match(&group.rasm)) {
+            continue;
+        }
+        let realisations: Vec<String> = group.realisations.iter().map(|(tok, n)| format!("{}:{}", tok, n)).collect();
+        writeln!(out, "{}\t{}\t{}\t{}", group.rasm, group.total, group.realisations.len(), realisations.join(" "))?;
+    }
+
+    out.flush()
+}
+
+fn candidates_command(args: Vec<String>) -> Result<()> {
+
+    let mut queries: Vec<String> = vec![];
+    let mut lexicon = String::new();
+    let mut range = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut limit = 100000usize;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("generate the dotted consonantal strings that a rasm may represent, \
+                                optionally only those attested in a word list or in the Quran");
+
+        parser.refer(&mut queries)
+                    .add_argument("rasm", List,
+                    "rasm in Latin archigraphemes, eg. KBBKG, or in Arabic script, eg. ڪٮٮكح").required();
+        parser.refer(&mut lexicon)
+                    .add_option(&["-l", "--lexicon"], Store,
+                    "keep only candidates found in this word list");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "keep only candidates found in the quranic range, eg. all");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple] [only for --quran]");
+        parser.refer(&mut limit)
+                    .add_option(&["--limit"], Store,
+                    "maximum number of candidates generated without lexicon [DEFAULT 100000]");
+        parse_subcommand_args(parser, args);
+    }
+
+    if !lexicon.is_empty() && !range.is_empty() {
+        eprintln!("Error: --lexicon and --quran cannot be used together");
+        process::exit(2);
+    }
+
+    let conv = Converter::new();
+
+    let lexicon = if !lexicon.is_empty() {
+        Some(Lexicon::from_words(&conv, open_input(&lexicon)?)?)
+    } else if !range.is_empty() {
+        let range = parse_range(&range);
+        Some(Lexicon::from_mushaf(&conv, &load_mushaf("", &source)?, Some(&range)))
+    } else {
+        None
+    };
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for query in &queries {
+
+        let rasm = dotting::query_rasm(&conv, query);
+
+        let result = match &lexicon {
+            _ if rasm.is_empty() => Err(DottingError::Empty { query: query.clone() }),
+            Some(lexicon) => lexicon.candidates(&rasm).map(|cands| {
+                cands.into_iter().map(|(cand, n)| format!("{}\t{}\t{}", rasm, cand, n)).collect::<Vec<_>>()
+            }),
+            None => dotting::candidates(&rasm, limit).map(|cands| {
+                cands.into_iter().map(|cand| format!("{}\t{}", rasm, cand)).collect()
+            }),
+        };
+
+        match result {
+            Ok(lines) if lines.is_empty() => {
+                out.flush()?;
+                eprintln!("# no candidates for {}", rasm);
+            }
+            Ok(lines) => for line in lines {
+                writeln!(out, "{}", line)?;
+            },
+            Err(err) => {
+                out.flush()?;
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+        }
+    }
+
+    out.flush()
+}
+
+fn restore_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut corpus = String::new();
+    let mut range = "all".to_string();
+    let mut source = "tanzil-simple".to_string();
+    let mut nalt = 3usize;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("restore dots of undotted text with a bigram model of dotted tokens. For each word it prints \
+                                its rasm, the most probable dotted form, its probability and the alternatives; \
+                                lines of input are separated by an empty line");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "undotted text, in Arabic script or in Latin archigraphemes with no spaces inside words [DEFAULT stdin]");
+        parser.refer(&mut corpus)
+                    .add_option(&["-c", "--corpus"], Store,
+                    "dotted text to train the model on, one sentence per line [DEFAULT the Quran]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to train the model on, if no corpus is given [DEFAULT all]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut nalt)
+                    .add_option(&["-n", "--alternatives"], Store,
+                    "maximum number of alternatives printed for each word [DEFAULT 3]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+
+    let mut model = DottingModel::new();
+    if !corpus.is_empty() {
+        model.train_text(&conv, open_input(&corpus)?)?;
+    } else {
+        let range = parse_range(&range);
+        model.train_mushaf(&conv, &load_mushaf("", &source)?, Some(&range));
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut failed = false;
+
+    for (iline, line) in open_input(&infile)?.lines().enumerate() {
+        let line = line?;
+        if iline > 0 {
+            writeln!(out)?;
+        }
+
+        // Latin rasm follows the same rules as --latin; Arabic script must have some letter to reduce
+        let words: Vec<&str> = line.split_whitespace().filter(|word| {
+            let valid = if word.is_ascii() {
+                latin::parse_word(&conv, word).map(|_| ()).map_err(|err| err.to_string())
+            } else if dotting::query_rasm(&conv, word).is_empty() {
+                Err(format!("\"{}\" has no Arabic letters", word))
+            } else {
+                Ok(())
+            };
+            valid.map_err(|err| {
+                eprintln!("line {}: {}", iline+1, err);
+                failed = true;
+            }).is_ok()
+        }).collect();
+
+        for word in model.restore(&conv, words) {
+            let alternatives: Vec<String> = word.alternatives.iter().take(nalt).map(|(alt, p)| format!("{}:{:.3}", alt, p)).collect();
+            writeln!(out, "{}\t{}\t{:.3}\t{}", word.rasm, word.best.unwrap_or_default(), word.confidence, alternatives.join(" "))?;
+        }
+    }
+
+    out.flush()?;
+
+    if failed {
+        process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn segment_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut lexicon = String::new();
+    let mut range = "all".to_string();
+    let mut source = "tanzil-simple".to_string();
+    let mut k = 3usize;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("group streams of letterblocks into the most probable sequence of rasm words. \
+                                For each line of input it prints the best segmentations with their rank, log probability, \
+                                number of unknown words and the words in Latin and Arabic archigraphemes; \
+                                lines of input are separated by an empty line");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "stream of blocks, in Latin archigraphemes separated by spaces or in Arabic script [DEFAULT stdin]");
+        parser.refer(&mut lexicon)
+                    .add_option(&["-l", "--lexicon"], Store,
+                    "word list or corpus in Arabic script to take the vocabulary from [DEFAULT the Quran]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to take the vocabulary from, if no lexicon is given [DEFAULT all]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut k)
+                    .add_option(&["-k", "--alternatives"], Store,
+                    "number of segmentations printed for each line [DEFAULT 3]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+
+    let voc = if !lexicon.is_empty() {
+        Vocabulary::from_words(&conv, open_input(&lexicon)?)?
+    } else {
+        let range = parse_range(&range);
+        Vocabulary::from_mushaf(&conv, &load_mushaf("", &source)?, Some(&range))
+    };
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for (iline, line) in open_input(&infile)?.lines().enumerate() {
+        let line = line?;
+        if iline > 0 {
+            writeln!(out)?;
+        }
+        let blocks = segment::stream_blocks(&conv, &line);
+        let blocks: Vec<&str> = blocks.iter().map(|b| b.as_str()).collect();
+        for (rank, seg) in voc.segment(&blocks, k).into_iter().enumerate() {
+            let lat: Vec<String> = seg.words.iter().map(|w| w.replace(' ', "")).collect();
+            let ara: Vec<String> = lat.iter().map(|w| conv.to_arabic(w).into_owned()).collect();
+            writeln!(out, "{}\t{:.3}\t{}\t{}\t{}", rank+1, seg.logprob, seg.unknown, lat.join(" "), ara.join(" "))?;
+        }
+    }
+
+    out.flush()
+}
+
+fn align_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut all = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("align a transcription with the closest stretch of the Quran at the letterblock level. \
+                                It prints the matched range and then one line for each variant: kind (sub, ins, del), \
+                                quranic index, quranic block in rasm and paleo, transcription block, \
+                                and quranic word in rasm and paleo as context");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "transcription in Arabic script or in Latin blocks separated by spaces [DEFAULT stdin]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut all)
+                    .add_option(&["-a", "--all"], StoreTrue,
+                    "print also the blocks that match");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+    let quran = load_mushaf("", &source)?;
+    let blocks = align::quran_blocks(&conv, &quran);
+
+    let mut text = String::new();
+    open_input(&infile)?.read_to_string(&mut text)?;
+    let ms = segment::stream_blocks(&conv, &text);
+
+    let alignment = match align::align(&blocks, &ms) {
+        Some(alignment) => alignment,
+        None => {
+            eprintln!("nothing to align");
+            process::exit(1);
+        }
+    };
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    writeln!(out, "range\t{}-{}\t{:.3}\t{} blocks", format_index(&blocks[alignment.start].index),
+             format_index(&blocks[alignment.end].index), alignment.distance, ms.len())?;
+
+    // quranic block of reference for insertions is the last one seen
+    let mut last = alignment.start;
+
+    for step in &alignment.steps {
+        if step.edit == Edit::Match && !all {
+            last = step.quran.unwrap();
+            continue;
+        }
+        let (lat, pal) = match step.quran {
+            Some(q) => {
+                last = q;
+                (blocks[q].lat.as_str(), blocks[q].pal.as_str())
+            }
+            None => ("-", "-"),
+        };
+        let ms_block = step.ms.map_or("-", |s| ms[s].as_str());
+
+        let [sura, vers, word, _] = blocks[last].index;
+        let word_blocks: Vec<_> = quran.ind[sura-1][vers-1][word-1].iter().map(|&itok| &quran.tok[itok].1).collect();
+        let word_lat: Vec<_> = word_blocks.iter().map(|pal| conv.strip_paleo(pal)).filter(|b| !b.is_empty()).collect();
+        let word_pal: Vec<_> = word_blocks.iter().map(|pal| pal.as_str()).filter(|b| !b.is_empty()).collect();
+
+        writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}\t{}", step.edit.name(), format_index(&blocks[last].index),
+                 lat, pal, ms_block, word_lat.join(" "), word_pal.join(" "))?;
+    }
+
+    out.flush()
+}
+
+fn collate_command(args: Vec<String>) -> Result<()> {
+
+    let mut file_a = String::new();
+    let mut file_b = String::new();
+    let mut apparatus = false;
+    let mut sigla = "A,B".to_string();
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("collate two Arabic texts by rasm, aligning them word by word and block by block. \
+                                Each difference is classified as skeleton, dotting or vocalisation. The TSV output has \
+                                kind, word number in each witness, tokens, rasms and differing blocks");
+
+        parser.refer(&mut file_a)
+                    .add_argument("a", Store,
+                    "first witness, taken as base text").required();
+        parser.refer(&mut file_b)
+                    .add_argument("b", Store,
+                    "second witness").required();
+        parser.refer(&mut apparatus)
+                    .add_option(&["-a", "--apparatus"], StoreTrue,
+                    "print differences as a critical apparatus keyed to the first witness instead of TSV, with the siglum of each reading");
+        parser.refer(&mut sigla)
+                    .add_option(&["--sigla"], Store,
+                    "sigla of the witnesses, separated by comma [DEFAULT A,B]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+
+    let mut text_a = String::new();
+    open_input(&file_a)?.read_to_string(&mut text_a)?;
+    let mut text_b = String::new();
+    open_input(&file_b)?.read_to_string(&mut text_b)?;
+
+    let (a, b) = (collate::tokens(&conv, &text_a), collate::tokens(&conv, &text_b));
+    let mut sigla = sigla.split(',').map(str::trim);
+    let (siglum_a, siglum_b) = (sigla.next().unwrap_or("A"), sigla.next().unwrap_or("B"));
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    // last word of the first witness seen, to anchor additions of the second one
+    let mut last_a = 0;
+
+    for diff in collate::collate(&conv, &a, &b) {
+
+        let blocks: Vec<String> = diff.blocks.iter()
+            .map(|(x, y)| format!("{}/{}", x.as_deref().unwrap_or("-"), y.as_deref().unwrap_or("-")))
+            .collect();
+
+        if !apparatus {
+            writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}", diff.kind.name(),
+                     diff.a.map_or("-".to_string(), |i| (i+1).to_string()),
+                     diff.b.map_or("-".to_string(), |j| (j+1).to_string()),
+                     diff.a.map_or("-", |i| a[i].ori.as_str()), diff.b.map_or("-", |j| b[j].ori.as_str()),
+                     diff.a.map_or("-", |i| a[i].lat.as_str()), diff.b.map_or("-", |j| b[j].lat.as_str()),
+                     blocks.join(" "))?;
+            continue;
+        }
+
+        let note = match diff.kind {
+            Kind::Skeleton => format!("skeleton: {}", blocks.join(" ")),
+            kind => kind.name().to_string(),
+        };
+        match (diff.a, diff.b) {
+            (Some(i), Some(j)) => writeln!(out, "{} {} {} ] {} {} ({})", i+1, a[i].ori, siglum_a, b[j].ori, siglum_b, note)?,
+            (Some(i), None) => writeln!(out, "{} {} {} ] om. {} ({})", i+1, a[i].ori, siglum_a, siglum_b, note)?,
+            (None, Some(j)) => writeln!(out, "{} ] add. {} {} ({})", last_a, b[j].ori, siglum_b, note)?,
+            (None, None) => {},
+        }
+        if let Some(i) = diff.a {
+            last_a = i+1;
+        }
+    }
+
+    out.flush()
+}
+
+fn quotes_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut n = 3usize;
+    let mut min = 4usize;
+    let mut tolerance = 0.25f64;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("detect quranic quotations in an Arabic text by rasm, regardless of dots and vowels. \
+                                For each passage it prints line, byte offsets in the text, quranic range, \
+                                number of words, number of words with a different skeleton and the passage");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "Arabic text [DEFAULT stdin]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut n)
+                    .add_option(&["-n", "--ngram"], Store,
+                    "number of consecutive words that must match exactly to start a passage [DEFAULT 3]");
+        parser.refer(&mut min)
+                    .add_option(&["-m", "--min"], Store,
+                    "minimum number of words of a passage [DEFAULT 4]");
+        parser.refer(&mut tolerance)
+                    .add_option(&["-t", "--tolerance"], Store,
+                    "maximum difference between the rasm of two words taken as matching, \
+                     as edit distance divided by the length of the longest [DEFAULT 0.25]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+    let quran = load_mushaf("", &source)?;
+    let index = QuoteIndex::build(&conv, &quran, n);
+
+    let mut text = String::new();
+    open_input(&infile)?.read_to_string(&mut text)?;
+    let toks = quote::text_tokens(&conv, &text);
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for found in index.find(&toks, min, tolerance) {
+        let (first, last) = (&toks[found.text.0], &toks[found.text.1]);
+        let passage = text[first.start..last.end].replace('\n', " ");
+        writeln!(out, "{}\t{}-{}\t{}-{}\t{}\t{}\t{}", first.line, first.start, last.end,
+                 format_index(&found.quran.0), format_index(&found.quran.1), found.words, found.diffs, passage)?;
+    }
+
+    out.flush()
+}
+
+/// Append to files the regular files under path, recursively and in name order if path is a directory.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = fs::read_dir(path)?.map(|e| e.map(|e| e.path())).collect::<Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            collect_files(&entry, files)?;
+        }
+    } else {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn dedup_command(args: Vec<String>) -> Result<()> {
+
+    let mut paths: Vec<String> = vec![];
+    let mut list = String::new();
+    let mut shingle = 3usize;
+    let mut num_perm = 128usize;
+    let mut bands = 32usize;
+    let mut threshold = 0.8f64;
+    let mut seed = 1u64;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("find clusters of near-duplicate Arabic documents by MinHash signatures of shingles \
+                                of rasm words, so that differences in dots, vowels and orthography are ignored. \
+                                For each document in a cluster it prints cluster number, cluster size, \
+                                estimated Jaccard similarity with the first document of the cluster and path");
+
+        parser.refer(&mut paths)
+                    .add_argument("paths", List,
+                    "documents, or directories searched recursively for documents");
+        parser.refer(&mut list)
+                    .add_option(&["-l", "--list"], Store,
+                    "file with one document path per line, \"-\" for stdin");
+        parser.refer(&mut shingle)
+                    .add_option(&["-k", "--shingle"], Store,
+                    "number of consecutive words per shingle [DEFAULT 3]");
+        parser.refer(&mut num_perm)
+                    .add_option(&["-p", "--permutations"], Store,
+                    "number of hash values per signature [DEFAULT 128]");
+        parser.refer(&mut bands)
+                    .add_option(&["-b", "--bands"], Store,
+                    "number of bands of the signature used to find candidate pairs; \
+                     more bands find pairs of lower similarity at a higher cost [DEFAULT 32]");
+        parser.refer(&mut threshold)
+                    .add_option(&["-t", "--threshold"], Store,
+                    "minimum estimated Jaccard similarity of near-duplicates [DEFAULT 0.8]");
+        parser.refer(&mut seed)
+                    .add_option(&["--seed"], Store,
+                    "seed of the hash permutations [DEFAULT 1]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let mut files = vec![];
+    for path in &paths {
+        collect_files(Path::new(path), &mut files)?;
+    }
+    if !list.is_empty() {
+        for line in open_input(&list)?.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                files.push(PathBuf::from(line.trim()));
+            }
+        }
+    }
+
+    let conv = Converter::new();
+    let hasher = MinHasher::new(num_perm, shingle, seed);
+
+    // documents are read one at a time and only their signatures kept, num_perm * 4 bytes each
+    let mut signatures = Vec::with_capacity(files.len());
+    for file in &files {
+        let words = match open_input(&file.to_string_lossy()).and_then(|fp| minhash::rasm_words(&conv, fp)) {
+            Ok(words) => words,
+            Err(err) => {
+                eprintln!("Warning: skipping {}: {}", file.display(), err);
+                signatures.push(None);
+                continue;
+            }
+        };
+        let signature = hasher.signature(&words);
+        if signature.is_none() {
+            eprintln!("Warning: no Arabic words in {}", file.display());
+        }
+        signatures.push(signature);
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for (n, cluster) in minhash::clusters(&signatures, bands, threshold).iter().enumerate() {
+        for &(doc, jaccard) in cluster {
+            writeln!(out, "{}\t{}\t{:.3}\t{}", n+1, cluster.len(), jaccard, files[doc].display())?;
+        }
+    }
+
+    out.flush()
+}
+
+fn ngrams_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut range = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut unit = "archigrapheme".to_string();
+    let mut n = 2usize;
+    let mut capacity = 1_000_000usize;
+    let mut top = 0usize;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("count archigrapheme, letterblock or rasm word n-grams of an Arabic text in a single pass. \
+                                Prints n-gram, count and relative frequency, most frequent first");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "Arabic text [DEFAULT stdin]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to count instead of input text");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut unit)
+                    .add_option(&["-u", "--unit"], Store,
+                    "unit of the n-grams: archigrapheme (inside words), block or word [DEFAULT archigrapheme]");
+        parser.refer(&mut n)
+                    .add_option(&["-n"], Store,
+                    "number of units per n-gram [DEFAULT 2]");
+        parser.refer(&mut capacity)
+                    .add_option(&["-c", "--capacity"], Store,
+                    "maximum number of distinct n-grams kept in memory; beyond it the least frequent are \
+                     discarded and counts become approximate [DEFAULT 1000000]");
+        parser.refer(&mut top)
+                    .add_option(&["--top"], Store,
+                    "print only the most frequent n-grams [DEFAULT all]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let unit = Unit::from_str(&unit).unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        process::exit(2);
+    });
+
+    let conv = Converter::new();
+    let mut counter = NgramCounter::new(unit, n, capacity);
+
+    if !range.is_empty() {
+        let range = parse_range(&range);
+        counter.add_mushaf(&conv, &load_mushaf("", &source)?, Some(&range));
+    } else {
+        counter.add_text(&conv, open_input(&infile)?)?;
+    }
+
+    if counter.error() > 0 {
+        eprintln!("Warning: capacity exceeded, counts may fall short by up to {}", counter.error());
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let total = counter.total().max(1) as f64;
+    let grams = counter.sorted();
+    let top = if top == 0 { grams.len() } else { top };
+
+    for (gram, count) in grams.into_iter().take(top) {
+        writeln!(out, "{}\t{}\t{:.6}", gram, count, count as f64 / total)?;
+    }
+
+    out.flush()
+}
+
+fn kwic_command(args: Vec<String>) -> Result<()> {
+
+    let mut query = String::new();
+    let mut infile = String::new();
+    let mut range = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut blocks = false;
+    let mut context = 5usize;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("keyword in context concordance by rasm. The query is an Arabic word, dotted or not, \
+                                which is reduced to archigraphemes, or a pattern of Latin archigraphemes as in search. \
+                                For each occurrence it prints line number or quranic index, left context, word, \
+                                right context and rasm of the match");
+
+        parser.refer(&mut query)
+                    .add_argument("query", Store,
+                    "Arabic word or Latin rasm pattern").required();
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "Arabic text [DEFAULT stdin]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "search the quranic range instead of input text");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut blocks)
+                    .add_option(&["-b", "--blocks"], StoreTrue,
+                    "match the query against each letterblock instead of whole words");
+        parser.refer(&mut context)
+                    .add_option(&["-c", "--context"], Store,
+                    "number of words of context on each side [DEFAULT 5]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+    let pattern = parse_pattern(&conv, &query);
+
+    let (words, range) = if !range.is_empty() {
+        let range = parse_range(&range);
+        (concordance::mushaf_words(&conv, &load_mushaf("", &source)?), Some(range))
+    } else {
+        let mut text = String::new();
+        open_input(&infile)?.read_to_string(&mut text)?;
+        (concordance::text_words(&conv, &text), None)
+    };
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let keep = |word: &concordance::Word| range.as_ref().is_none_or(|range| range.contains(&word.pos));
+
+    for hit in concordance::concordance(&words, &pattern, blocks, context, keep) {
+        let left: Vec<_> = hit.left.iter().map(|w| w.ori.as_str()).collect();
+        let right: Vec<_> = hit.right.iter().map(|w| w.ori.as_str()).collect();
+        let (pos, lat) = match hit.block {
+            Some(iblock) => (format!("{}:{}", format_index(&hit.word.pos), iblock),
+                             hit.word.lat.split_whitespace().nth(iblock-1).unwrap()),
+            None => (format_index(&hit.word.pos), hit.word.lat.as_str()),
+        };
+        writeln!(out, "{}\t{}\t{}\t{}\t{}", pos, left.join(" "), hit.word.ori, right.join(" "), lat)?;
+    }
+
+    out.flush()
+}
+
+fn positions_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+    let mut range = String::new();
+    let mut source = "tanzil-simple".to_string();
+    let mut total = false;
+    let mut json = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("count each archigrapheme in isolated, initial, medial and final position inside the \
+                                letterblock, for a whole text or for each sura of a quranic range. Prints a TSV matrix \
+                                of archigraphemes by position, with a leading sura column for the Quran, or JSON");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "Arabic text [DEFAULT stdin]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to count instead of input text, sura by sura");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut total)
+                    .add_option(&["-t", "--total"], StoreTrue,
+                    "add up the suras of the quranic range into a single matrix");
+        parser.refer(&mut json)
+                    .add_option(&["-j", "--json"], StoreTrue,
+                    "print JSON instead of TSV");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+
+    // matrices by sura, or a single one with no sura for a text or the total of a range
+    let mut matrices: Vec<(Option<usize>, PositionStats)> = vec![];
+
+    if !range.is_empty() {
+        let range = parse_range(&range);
+        let suras = positions::sura_stats(&conv, &load_mushaf("", &source)?, Some(&range));
+        if total {
+            let mut stats = PositionStats::new();
+            suras.values().for_each(|sura| stats.merge(sura));
+            matrices.push((None, stats));
+        } else {
+            matrices.extend(suras.into_iter().map(|(sura, stats)| (Some(sura), stats)));
+        }
+    } else {
+        let mut stats = PositionStats::new();
+        stats.add_text(&conv, open_input(&infile)?)?;
+        matrices.push((None, stats));
+    }
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    if json {
+        let value = match &matrices[..] {
+            [(None, stats)] => serde_json::to_value(stats)?,
+            _ => serde_json::to_value(matrices.iter()
+                    .map(|(sura, stats)| (sura.unwrap(), stats))
+                    .collect::<BTreeMap<_, _>>())?,
+        };
+        serde_json::to_writer_pretty(&mut out, &value)?;
+        writeln!(out)?;
+        return out.flush();
+    }
+
+    let sura_column = matrices.iter().any(|(sura, _)| sura.is_some());
+    writeln!(out, "{}archigrapheme\tisolated\tinitial\tmedial\tfinal\ttotal", if sura_column { "sura\t" } else { "" })?;
+
+    for (sura, stats) in &matrices {
+        let prefix = sura.map(|sura| format!("{}\t", sura)).unwrap_or_default();
+        for (arch, counts) in stats.rows() {
+            writeln!(out, "{}{}\t{}\t{}\t{}\t{}\t{}", prefix, arch,
+                     counts.isolated, counts.initial, counts.medial, counts.fin, counts.total())?;
+        }
+    }
+
+    out.flush()
+}
+
+/// Write a row of the density report: identifiers of the unit, counts and ratios to the archigraphemes.
+fn write_density<W: Write>(out: &mut W, ids: &[&str], density: &Density) -> Result<()> {
+    let counts: Vec<_> = density.signs().iter().map(|n| n.to_string()).collect();
+    let ratios: Vec<_> = density.ratios().iter().map(|r| format!("{:.3}", r)).collect();
+    writeln!(out, "{}\t{}\t{}\t{}", ids.join("\t"), density.archigraphemes, counts.join("\t"), ratios.join("\t"))
+}
+
+fn density_command(args: Vec<String>) -> Result<()> {
+
+    let mut infiles: Vec<String> = vec![];
+    let mut range = String::new();
+    let mut source = "tanzil-uthmani".to_string();
+    let mut level = "line".to_string();
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("count consonantal dots, short vowels, shadda, sukun, hamza and quranic annotation marks \
+                                per token, line or document, and their ratio to the number of archigraphemes. \
+                                For the Quran, documents are suras, lines are verses and tokens are words");
+
+        parser.refer(&mut infiles)
+                    .add_argument("infiles", List,
+                    "Arabic texts [DEFAULT stdin]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to measure instead of input texts");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-uthmani]");
+        parser.refer(&mut level)
+                    .add_option(&["-l", "--level"], Store,
+                    "unit of the report: token, line or document [DEFAULT line]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let ids = match level.as_str() {
+        "token" => "document\tline\ttoken",
+        "line" => "document\tline",
+        "document" => "document",
+        _ => {
+            eprintln!("Error: unknown level \"{}\", expected token, line or document", level);
+            process::exit(2);
+        }
+    };
+
+    let conv = Converter::new();
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let ratios: Vec<_> = density::SIGNS.iter().map(|sign| format!("{}/arch", sign)).collect();
+    writeln!(out, "{}\tarchigraphemes\t{}\t{}", ids, density::SIGNS.join("\t"), ratios.join("\t"))?;
+
+    // documents as (name, lines), lines as (name, tokens)
+    type Line = (String, Vec<String>);
+    let mut documents: Vec<(String, Vec<Line>)> = vec![];
+
+    if !range.is_empty() {
+        let range = parse_range(&range);
+        let quran = load_mushaf("", &source)?;
+        for ((sura, vers, word), blocks) in quran.words() {
+            if !range.contains(&[sura, vers, word]) {
+                continue;
+            }
+            let tok: String = blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect();
+            if documents.last().is_none_or(|(name, _)| *name != sura.to_string()) {
+                documents.push((sura.to_string(), vec![]));
+            }
+            let lines = &mut documents.last_mut().unwrap().1;
+            let name = format!("{}:{}", sura, vers);
+            if lines.last().is_none_or(|(line, _)| *line != name) {
+                lines.push((name, vec![]));
+            }
+            lines.last_mut().unwrap().1.push(tok);
+        }
+    } else {
+        if infiles.is_empty() {
+            infiles.push("-".to_string());
+        }
+        for infile in &infiles {
+            let mut lines = vec![];
+            for (i, line) in open_input(infile)?.lines().enumerate() {
+                let line = line?;
+                lines.push(((i+1).to_string(), conv.tokenise(&line).map(String::from).collect()));
+            }
+            documents.push((infile.clone(), lines));
+        }
+    }
+
+    for (document, lines) in &documents {
+        let mut document_density = Density::default();
+        for (line, toks) in lines {
+            let mut line_density = Density::default();
+            for tok in toks {
+                let density = Density::of_token(&conv, tok);
+                // signs standing on their own, like pause marks between spaces, count only for the line
+                if level == "token" && density.archigraphemes > 0 {
+                    write_density(&mut out, &[document, line, tok], &density)?;
+                }
+                line_density.add(&density);
+            }
+            if level == "line" {
+                write_density(&mut out, &[document, line], &line_density)?;
+            }
+            document_density.add(&line_density);
+        }
+        if level == "document" {
+            write_density(&mut out, &[document], &document_density)?;
+        }
+    }
+
+    out.flush()
+}
+
+fn rhyme_command(args: Vec<String>) -> Result<()> {
+
+    let mut range = "all".to_string();
+    let mut source = "tanzil-simple".to_string();
+    let mut n = 0usize;
+    let mut group = false;
+    let mut compare = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("analyse the verse-final rhyme (fasila) of the Quran by rasm. By default it prints the runs \
+                                of consecutive verses with the same rhyme, each run after the first of a sura being a change \
+                                of rhyme: sura, first-last verse, number of verses and rhyme");
+
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to analyse [DEFAULT all]");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple]");
+        parser.refer(&mut n)
+                    .add_option(&["-n"], Store,
+                    "take as rhyme the last n archigraphemes of the verse instead of its last letterblock");
+        parser.refer(&mut group)
+                    .add_option(&["-g", "--groups"], StoreTrue,
+                    "group the verses of each sura by rhyme: sura, rhyme, number of verses and verses");
+        parser.refer(&mut compare)
+                    .add_option(&["-c", "--compare"], StoreTrue,
+                    "compare tanzil-simple with tanzil-uthmani, printing the verses where rhymes differ or the rhyme \
+                     changes in only one of them: index, simple rhyme, uthmani rhyme and source where a change starts");
+        parse_subcommand_args(parser, args);
+    }
+
+    let range = parse_range(&range);
+    let conv = Converter::new();
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    if compare {
+        let simple = rhyme::endings(&conv, &load_mushaf("", "tanzil-simple")?, n, Some(&range));
+        let uthmani = rhyme::endings(&conv, &load_mushaf("", "tanzil-uthmani")?, n, Some(&range));
+        let divergences = rhyme::compare(&simple, &uthmani);
+        for div in &divergences {
+            let change = match (div.change_a, div.change_b) {
+                (true, true) => "both",
+                (true, false) => "simple",
+                (false, true) => "uthmani",
+                (false, false) => "",
+            };
+            writeln!(out, "{}:{}\t{}\t{}\t{}", div.sura, div.vers, div.rhyme_a, div.rhyme_b, change)?;
+        }
+        eprintln!("# {} of {} verses diverge", divergences.len(), simple.len());
+        return out.flush();
+    }
+
+    let endings = rhyme::endings(&conv, &load_mushaf("", &source)?, n, Some(&range));
+
+    if group {
+        for (sura, groups) in rhyme::groups(&endings) {
+            for (rhyme, verses) in groups {
+                let verses: Vec<_> = verses.iter().map(|v| v.to_string()).collect();
+                writeln!(out, "{}\t{}\t{}\t{}", sura, rhyme, verses.len(), verses.join(","))?;
+            }
+        }
+    } else {
+        for run in rhyme::runs(&endings) {
+            writeln!(out, "{}\t{}-{}\t{}\t{}", run.sura, run.first, run.last, run.verses(), run.rhyme)?;
+        }
+    }
+
+    out.flush()
+}
+
+fn compare_sources_command(args: Vec<String>) -> Result<()> {
+
+    let mut source_a = "tanzil-simple".to_string();
+    let mut source_b = "tanzil-uthmani".to_string();
+    let mut range = String::new();
+    let mut summary = false;
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("compare two sources of the quranic text word by word. For each word whose rasm, \
+                                letterblocks or paleo-orthographic form differ, or that is missing in one source, \
+                                it prints its index in each source, kind of difference, and token, rasm and paleo form \
+                                in each source");
+
+        parser.refer(&mut source_a)
+                    .add_argument("a", Store,
+                    "first source: tanzil-simple, tanzil-uthmani, decotype or a quran data struct in json [DEFAULT tanzil-simple]");
+        parser.refer(&mut source_b)
+                    .add_argument("b", Store,
+                    "second source [DEFAULT tanzil-uthmani]");
+        parser.refer(&mut range)
+                    .add_option(&["-q", "--quran"], Store,
+                    "compare only inside quranic range");
+        parser.refer(&mut summary)
+                    .add_option(&["--summary"], StoreTrue,
+                    "print only the counts per sura: sura, words, missing, rasm, blocks and paleo differences");
+        parse_subcommand_args(parser, args);
+    }
+
+    let load = |arg: &str| if arg.parse::<Source>().is_ok() { load_mushaf("", arg) } else { load_mushaf(arg, "") };
+    let (a, b) = (load(&source_a)?, load(&source_b)?);
+    let range = if range.is_empty() { None } else { Some(parse_range(&range)) };
+
+    let conv = Converter::new();
+    let comparison = sources::compare(&conv, &a, &b, range.as_ref());
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    if summary {
+        writeln!(out, "sura\twords\tmissing\trasm\tblocks\tpaleo")?;
+        let mut total = sources::SuraSummary::default();
+        for (sura, counts) in &comparison.suras {
+            writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}", sura, counts.words, counts.missing, counts.rasm, counts.blocks, counts.paleo)?;
+            total.words += counts.words;
+            total.missing += counts.missing;
+            total.rasm += counts.rasm;
+            total.blocks += counts.blocks;
+            total.paleo += counts.paleo;
+        }
+        writeln!(out, "total\t{}\t{}\t{}\t{}\t{}", total.words, total.missing, total.rasm, total.blocks, total.paleo)?;
+        return out.flush();
+    }
+
+    let empty = sources::SourceWord { tok: String::new(), lat: String::new(), pal: String::new() };
+    for diff in &comparison.diffs {
+        let (wa, wb) = (diff.a.as_ref().unwrap_or(&empty), diff.b.as_ref().unwrap_or(&empty));
+        let index = |index: Option<[usize; 3]>| index.map_or("-".to_string(), |index| format_index(&index));
+        writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}", index(diff.index_a), index(diff.index_b), diff.kind.name(),
+                 wa.tok, wb.tok, wa.lat, wb.lat, wa.pal, wb.pal)?;
+    }
+
+    out.flush()
+}
+
+fn from_paleo_command(args: Vec<String>) -> Result<()> {
+
+    let mut infile = String::new();
+
+    {
+        let mut parser = ArgumentParser::new();
+        parser.set_description("convert paleo-orthographic notation into Arabic text in NFC, eg. B¹ → ن, B² → ت, B₁ → ب. \
+                                Each whitespace-separated token is a word, so the letterblocks of a word must be written \
+                                together, eg. ALLH. Tokens that cannot be converted are reported with their line and kept as they are");
+
+        parser.refer(&mut infile)
+                    .add_argument("infile", Store,
+                    "text in paleo-orthographic notation [DEFAULT stdin]");
+        parse_subcommand_args(parser, args);
+    }
+
+    let conv = Converter::new();
+    let unpaleo = Unpaleo::new(&conv);
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut errors = 0;
+
+    for (i, line) in open_input(&infile)?.lines().enumerate() {
+        let line = line?;
+        let words: Vec<String> = line.split_whitespace().map(|tok| unpaleo.word(tok).unwrap_or_else(|err| {
+            eprintln!("line {}: {}", i+1, err);
+            errors += 1;
+            tok.to_string()
+        })).collect();
+        writeln!(out, "{}", words.join(" "))?;
+    }
+
+    out.flush()?;
+    if errors > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print the words of the quranic range with their archigraphemes and index, optionally with the
+/// original tokens transliterated.
+fn quran_command(range: &str, source: &str, translit: Option<&Transliterator>) -> Result<()> {
+
+    let range = parse_range(range);
+
+    // echo the range as understood, e.g. after resolving names and Arabic digits
+    eprintln!("# quran range: {}", range);
+
+    let quran = load_mushaf("", source)?;
+    let conv = Converter::new();
+
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    for ((sura, vers, word), blocks) in quran.words() {
+
+        let blocks: Vec<_> = blocks.iter().enumerate()
+            .filter(|&(iblock, _)| range.contains(&[sura, vers, word, iblock+1]))
+            .map(|(_, &itok)| &quran.tok[itok])
+            .collect();
+
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let mut tok: String = blocks.iter().map(|(tok, _)| tok.as_str()).collect();
+        if let Some(translit) = translit {
+            tok = translit.from_arabic(&tok).unwrap_or_else(|err| {
+                eprintln!("Error: {}", err);
+                process::exit(1);
+            });
+        }
+        let lat: Vec<_> = blocks.iter().map(|(_, pal)| conv.strip_paleo(pal)).filter(|b| !b.is_empty()).collect();
+        let ara: Vec<_> = lat.iter().map(|b| conv.to_arabic(b).into_owned()).collect();
+
+        writeln!(out, "{}\t{}\t{}\t{}:{}:{}", tok, lat.join(" "), ara.join(" "), sura, vers, word)?;
+    }
+
+    out.flush()
+}
+
+fn main() {
+    match run() {
+        Ok(()) => {},
+        // output closed early, eg. piped into head
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => {},
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run() -> Result<()> {
 
     let mut fname = String::new();
+    let mut quran = String::new();
+    let mut source = "tanzil-simple".to_string();
     let mut normalise = false;
     let mut tokenise = false;
     let mut uniq = false;
+    let mut latin = false;
+    let mut translit_in = String::new();
+    let mut translit_out = String::new();
+    let mut encoding = "auto".to_string();
+    let mut lossy = false;
     let mut version = false;
+    let mut command = Command::Convert;
+    let mut args: Vec<String> = vec![];
 
     {
         let mut parser = ArgumentParser::new();
@@ -648,7 +1665,16 @@ fn main() -> Result<()> {
         //FIXME add optionality stdin/file add stdout ; check clap: rust crate
         parser.refer(&mut fname)
                     .add_option(&["--infile"], Store,
-                    "input text");
+                    "input text [DEFAULT stdin]");
+        parser.refer(&mut quran)
+                    .add_option(&["-q", "--quran"], Store,
+                    "quranic range to convert instead of input text: comma-separated union of all, \
+                     i[:j[:k[:m]]][-n[:p[:q[:r]]]] (sura may be given by name, eg. al-Baqara:255, \
+                     and digits in Arabic-Indic, eg. ٢:٢٥٥) \
+                     or juz|hizb|manzil|page N[-M] (page of the Madina mushaf)");
+        parser.refer(&mut source)
+                    .add_option(&["-s", "--source"], Store,
+                    "source of the quranic text: tanzil-simple, tanzil-uthmani or decotype [DEFAULT tanzil-simple] [only for --quran]");
 
         //FIXME quitar normalise y tokenise !! que se hagan siempre
         parser.refer(&mut normalise)
@@ -661,10 +1687,39 @@ fn main() -> Result<()> {
         parser.refer(&mut uniq)
                     .add_option(&["-u", "--uniq"], StoreTrue,
                     "output each unique archigraphemic letterblock, number of total occurrences and list of unique occurrences");
+        parser.refer(&mut latin)
+                    .add_option(&["-l", "--latin"], StoreTrue,
+                    "input is rasm in Latin archigraphemes instead of Arabic script, eg. FAL ALLH, \
+                     or tab-separated words with spaces between letterblocks, eg. FAL<TAB>A LLH");
+        parser.refer(&mut translit_in)
+                    .add_option(&["-i", "--input-translit"], Store,
+                    "input is transliterated Arabic: buckwalter, safe-buckwalter, or buckwalter-quran and \
+                     safe-buckwalter-quran, which also read the Quranic marks at the cost of some punctuation \
+                     (so the latter is not safe in XML, regexes or the shell)");
+        parser.refer(&mut translit_out)
+                    .add_option(&["-o", "--output-translit"], Store,
+                    "write the original tokens transliterated: buckwalter, safe-buckwalter, buckwalter-quran \
+                     or safe-buckwalter-quran");
+        parser.refer(&mut encoding)
+                    .add_option(&["-e", "--encoding"], Store,
+                    "encoding of the input of every command, given before the subcommand: auto, utf-8, utf-16le, utf-16be, \
+                     cp1256 or iso-8859-6. auto follows the byte order mark, or else guesses between utf-8, cp1256 \
+                     and iso-8859-6 from the first 8 KB [DEFAULT auto]");
+        parser.refer(&mut lossy)
+                    .add_option(&["--lossy"], StoreTrue,
+                    "replace undecodable sequences of the input with U+FFFD and report their line and byte offset, instead of failing");
         
         parser.refer(&mut version)
                     .add_option(&["--version"], StoreTrue,
                     "prints the program version number and exits successfully");
+
+        parser.refer(&mut command)
+                    .add_argument("command", Store,
+                    "optional subcommand: build, verify, index, search, homographs, candidates, restore, segment, align, collate, quotes, dedup, ngrams, kwic, positions, density, rhyme, compare-sources, from-paleo");
+        parser.refer(&mut args)
+                    .add_argument("arguments", List,
+                    "arguments for subcommand");
+        parser.stop_on_first_argument(true);
         parser.parse_args_or_exit();
     }
 
@@ -673,113 +1728,206 @@ fn main() -> Result<()> {
         process::exit(0);
     }
 
-    /*
-     * load data
-     */
+    let encoding = if encoding == "auto" {
+        None
+    } else {
+        Some(encoding.parse().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        }))
+    };
+    INPUT_ENCODING.get_or_init(|| InputEncoding { encoding, lossy });
 
-    let chars = load_arabic_inventory();
-    let mappings = load_rasm_mappings(&chars);
-
-    /*
-     * compile regexes
-     */
-
-    let re_norm = Regex::new(&chars.clusters.keys().map(|s| &**s).collect::<Vec<_>>().join("|")).unwrap(); //FIXME <Vec<_>> ??
+    match command {
+        Command::Convert => {},
+        Command::Build => {
+            args.insert(0, "rasm build".to_string());
+            return build_command(args);
+        }
+        Command::Verify => {
+            args.insert(0, "rasm verify".to_string());
+            return verify_command(args);
+        }
+        Command::Index => {
+            args.insert(0, "rasm index".to_string());
+            return index_command(args);
+        }
+        Command::Search => {
+            args.insert(0, "rasm search".to_string());
+            return search_command(args);
+        }
+        Command::Homographs => {
+            args.insert(0, "rasm homographs".to_string());
+            return homographs_command(args);
+        }
+        Command::Candidates => {
+            args.insert(0, "rasm candidates".to_string());
+            return candidates_command(args);
+        }
+        Command::Restore => {
+            args.insert(0, "rasm restore".to_string());
+            return restore_command(args);
+        }
+        Command::Segment => {
+            args.insert(0, "rasm segment".to_string());
+            return segment_command(args);
+        }
+        Command::Align => {
+            args.insert(0, "rasm align".to_string());
+            return align_command(args);
+        }
+        Command::Collate => {
+            args.insert(0, "rasm collate".to_string());
+            return collate_command(args);
+        }
+        Command::Quotes => {
+            args.insert(0, "rasm quotes".to_string());
+            return quotes_command(args);
+        }
+        Command::Dedup => {
+            args.insert(0, "rasm dedup".to_string());
+            return dedup_command(args);
+        }
+        Command::Ngrams => {
+            args.insert(0, "rasm ngrams".to_string());
+            return ngrams_command(args);
+        }
+        Command::Kwic => {
+            args.insert(0, "rasm kwic".to_string());
+            return kwic_command(args);
+        }
+        Command::Positions => {
+            args.insert(0, "rasm positions".to_string());
+            return positions_command(args);
+        }
+        Command::Density => {
+            args.insert(0, "rasm density".to_string());
+            return density_command(args);
+        }
+        Command::Rhyme => {
+            args.insert(0, "rasm rhyme".to_string());
+            return rhyme_command(args);
+        }
+        Command::CompareSources => {
+            args.insert(0, "rasm compare-sources".to_string());
+            return compare_sources_command(args);
+        }
+        Command::FromPaleo => {
+            args.insert(0, "rasm from-paleo".to_string());
+            return from_paleo_command(args);
+        }
+    }
 
-    let re_ara = Regex::new(&format!("[^{A}{N}{Y}{B}{G}{R}{D}{T}{C}{S}{Q}{F}{E}{W}{H}{M}{L}{K}{diac} ]",
-                            A=chars.A, N=chars.N, Y=chars.Y, B=chars.B, G=chars.G, R=chars.R, D=chars.D,
-                            T=chars.T, C=chars.C, S=chars.S, Q=chars.Q, F=chars.F, E=chars.E, W=chars.W,
-                            H=chars.H, M=chars.M, L=chars.L, K=chars.K, diac=chars.diac)).unwrap();
+    let translit_in = parse_translit(&translit_in);
+    let translit_out = parse_translit(&translit_out);
 
-    let re_clean = Regex::new(&format!("[{}]", chars.diac)).unwrap();
+    if latin && translit_in.is_some() {
+        eprintln!("Error: --latin and --input-translit cannot be used together");
+        process::exit(2);
+    }
 
-    let re_rasm_end = Regex::new(&format!("({})$", mappings.mapping_end.keys().map(|s| &**s).collect::<Vec<_>>().join("|")) ).unwrap();
+    if !quran.is_empty() {
+        return quran_command(&quran, &source, translit_out.as_ref());
+    }
 
-    let re_rasm_gen = Regex::new(&mappings.mapping_gen.keys().map(|s| &**s).collect::<Vec<_>>().join("|")).unwrap();
-    let re_rasm_ara = Regex::new(&mappings.mapping_ara.keys().map(|s| &**s).collect::<Vec<_>>().join("|")).unwrap();
+    /*
+     * load data and compile regexes
+     */
 
+    let conv = Converter::new();
 
     /*
-     * prepare containers to index data
+     * unique letterblocks: Latin -> (Arabic, occurrences, tokens where they appear)
      */
 
-    //FIXME add arg check for uniq
-    let mut letterblock_ara = HashMap::new();
-    //let mut letterblock_count = HashMap::new();
-    //let mut letterblock_tokens = HashMap::new();
+    let mut letterblocks: HashMap<String, (String, usize, BTreeSet<String>)> = HashMap::new();
+    let mut failed = false;
 
     /*
      * process file
      */
 
-    let fp = File::open(fname)?;
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
 
-    for line in  BufReader::new(fp).lines() {
-        let line_read = line.unwrap();
-        
-        /* normalise */
+    for (iline, line) in open_input(&fname)?.lines().enumerate() {
+        let line = line?;
 
-        let line_norm = re_norm.replace_all(&line_read, |cap: &Captures| {
-            chars.clusters.get(cap.get(0).unwrap().as_str()).unwrap()
-        });
+        let words: Vec<Reduced> = if latin {
 
-        /* tokenise */
+            /* parse Latin archigraphemes */
 
-        let line_clean = re_ara.replace_all(&line_norm, " ");
+            latin::split_words(&line).into_iter().filter_map(|word| {
+                latin::parse_word(&conv, word).map_err(|err| {
+                    eprintln!("line {}: {}", iline+1, err);
+                    failed = true;
+                }).ok()
+            }).collect()
 
-        for tok in line_clean.split_whitespace() {
+        } else {
 
-            let tok_clean = re_clean.replace_all(&tok, "");
+            /* transliterate */
 
-            /* reduce */
+            let line = match &translit_in {
+                Some(translit) => Cow::Owned(translit.to_arabic(&line)),
+                None => Cow::Borrowed(line.as_str()),
+            };
 
-            let tok_reduced_lat = re_rasm_end.replace_all(&tok_clean, |cap: &Captures| {
-                mappings.mapping_end.get(cap.get(0).unwrap().as_str()).unwrap()
-            });
+            /* normalise */
 
-            let tok_reduced_lat = re_rasm_gen.replace_all(&tok_reduced_lat, |cap: &Captures| {
-                mappings.mapping_gen.get(cap.get(0).unwrap().as_str()).unwrap()
-            });
-
-            let tok_reduced_ara = re_rasm_ara.replace_all(&tok_reduced_lat, |cap: &Captures| {
-                mappings.mapping_ara.get(cap.get(0).unwrap().as_str()).unwrap()
-            });
-
-            /*
-             * normal output
-             */
+            let line_norm = conv.normalise(&line);
 
-            println!("{}\t{}\t{}", tok_clean, tok_reduced_lat, tok_reduced_ara); //DEBUG  الله A LLH   ا لله
+            /* tokenise */
 
-            /*
-             * uniq output
-             */
+            let line_clean = conv.re_ara.replace_all(&line_norm, " ");
 
-            //FIXME add arg check for uniq
-            for zipped in tok_reduced_lat.split_whitespace().zip(tok_reduced_ara.split_whitespace()) {
-                //let (letterblock_lat, letterblock_ara) = zipped; //FIXME
-                let letterblock_lat = zipped.0.to_string(); ///////////////////////////
-                let letterblock_ara = zipped.1.to_string(); ///////////////////////////
+            /* reduce */
 
-                //println!("{}", zipped); //DEBUG
+            line_clean.split_whitespace().map(|tok| conv.reduce(&conv.re_clean.replace_all(tok, ""))).collect()
+        };
 
-                //println!("{} {}", letterblock_lat, letterblock_ara); //DEBUG
+        for mut word in words {
 
-                letterblock_ara.insert(letterblock_lat.to_string(), letterblock_ara.to_string());
-                //letterblock_count.insert(letterblock_lat, 0);  //FIXME
+            if let Some(translit) = &translit_out {
+                match translit.from_arabic(&word.ori) {
+                    Ok(ori) => word.ori = ori,
+                    Err(err) => {
+                        eprintln!("line {}: {}", iline+1, err);
+                        failed = true;
+                    }
+                }
+            }
 
-                //let count = map.entry(key).get().unwrap_or_else(|v| v.insert(0));
-                // *count += 1;
+            if !uniq {
+                writeln!(out, "{}\t{}\t{}", word.ori, word.lat, word.ara)?;
+                continue;
+            }
 
-                //letterblock_tokens.insert(letterblock_lat, []); //FIXME
-                
+            for (block_lat, block_ara) in word.lat.split_whitespace().zip(word.ara.split_whitespace()) {
+                let entry = letterblocks.entry(block_lat.to_string())
+                    .or_insert_with(|| (block_ara.to_string(), 0, BTreeSet::new()));
+                entry.1 += 1;
+                entry.2.insert(word.ori.clone());
             }
         }
     }
 
-    //TODO vamos a considerar que normalise, tokenise y reduce son obligatorios, se hacen siempre
-    // junta los resultados en una estructura
-    // opcionalmente, segun el usuario llame al comando con uniq, hacer ese procesado.
+    /*
+     * uniq output: most frequent letterblocks first
+     */
+
+    let mut letterblocks: Vec<_> = letterblocks.into_iter().collect();
+    letterblocks.sort_by(|(lat_a, (_, n_a, _)), (lat_b, (_, n_b, _))| n_b.cmp(n_a).then_with(|| lat_a.cmp(lat_b)));
+
+    for (lat, (ara, n, toks)) in letterblocks {
+        writeln!(out, "{}\t{}\t{}\t{}", lat, ara, n, toks.into_iter().collect::<Vec<_>>().join(" "))?;
+    }
+
+    out.flush()?;
+
+    if failed {
+        process::exit(1);
+    }
 
     Ok(())
 }
@@ -0,0 +1,216 @@
+/*
+ *    minhash.rs
+ *
+ * near-duplicate detection of documents by MinHash signatures of rasm shingles
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::io::{BufRead, Result};
+
+use crate::convert::Converter;
+
+/// Mersenne prime 2^61-1, modulus of the hash permutations.
+const PRIME: u64 = (1 << 61) - 1;
+
+/// Largest bucket of candidates in which every pair of documents is compared.
+const SMALL_BUCKET: usize = 64;
+
+/// Family of hash permutations used to compute MinHash signatures of word shingles.
+#[derive(Debug, Clone)]
+pub struct MinHasher {
+    coefs: Vec<(u64, u64)>,
+    shingle: usize,
+}
+
+impl MinHasher {
+
+    /// Hasher with signatures of num_perm values over shingles of the given number of words.
+    /// The permutations are generated from seed, so signatures are comparable only when computed
+    /// with the same parameters.
+    pub fn new(num_perm: usize, shingle: usize, seed: u64) -> MinHasher {
+        let mut state = seed;
+        let coefs = (0..num_perm)
+            .map(|_| (splitmix64(&mut state) % (PRIME - 1) + 1, splitmix64(&mut state) % PRIME))
+            .collect();
+        MinHasher { coefs, shingle: shingle.max(1) }
+    }
+
+    /// Signature of a document given by its sequence of rasm words. Documents shorter than a shingle
+    /// are taken as a single shingle; empty documents have no signature.
+    pub fn signature<S: AsRef<str>>(&self, words: &[S]) -> Option<Vec<u32>> {
+
+        if words.is_empty() {
+            return None;
+        }
+
+        let shingles: Vec<u64> = if words.len() < self.shingle {
+            vec![fnv1a(words.iter().map(|w| w.as_ref()))]
+        } else {
+            words.windows(self.shingle).map(|w| fnv1a(w.iter().map(|w| w.as_ref()))).collect()
+        };
+
+        Some(self.coefs.iter().map(|&(a, b)| {
+            let min = shingles.iter()
+                .map(|&x| ((a as u128 * (x % PRIME) as u128 + b as u128) % PRIME as u128) as u64)
+                .min()
+                .unwrap();
+            min as u32
+        }).collect())
+    }
+}
+
+/// Rasm of the words of a text, read line by line, without spaces between blocks, so that
+/// two documents differing only in dots, vowels or orthography give the same sequence.
+pub fn rasm_words<R: BufRead>(conv: &Converter, reader: R) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = conv.normalise(&line);
+        let line = conv.re_ara.replace_all(&line, " ");
+        for tok in line.split_whitespace() {
+            let lat: String = conv.reduce(tok).lat.split_whitespace().collect();
+            if !lat.is_empty() {
+                words.push(lat);
+            }
+        }
+    }
+    Ok(words)
+}
+
+/// Estimate of the Jaccard similarity of the shingle sets of two documents from their signatures.
+pub fn jaccard(a: &[u32], b: &[u32]) -> f64 {
+    a.iter().zip(b).filter(|(x, y)| x == y).count() as f64 / a.len().max(1) as f64
+}
+
+/// Group documents into clusters of near-duplicates. Candidate pairs are documents that share all
+/// the values of at least one band of their signatures (locality-sensitive hashing); pairs whose estimated
+/// Jaccard similarity reaches threshold are joined. Return the clusters of two or more documents, each as
+/// a list of (document, similarity with the first document), largest clusters first.
+pub fn clusters(signatures: &[Option<Vec<u32>>], bands: usize, threshold: f64) -> Vec<Vec<(usize, f64)>> {
+
+    let mut parent: Vec<usize> = (0..signatures.len()).collect();
+
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let len = signatures.iter().flatten().map(|s| s.len()).next().unwrap_or(0);
+    let bands = bands.clamp(1, len.max(1));
+    let rows = len / bands;
+
+    for band in 0..bands {
+        let mut buckets: HashMap<&[u32], Vec<usize>> = HashMap::new();
+        for (doc, sig) in signatures.iter().enumerate() {
+            if let Some(sig) = sig {
+                buckets.entry(&sig[band*rows..(band+1)*rows]).or_default().push(doc);
+            }
+        }
+        // small buckets compare every pair; larger ones compare each document with one representative of
+        // each cluster already found in the bucket, to keep them linear in bucket size times clusters
+        for docs in buckets.values().filter(|docs| docs.len() > 1) {
+            let mut reps: Vec<usize> = Vec::new();
+            for (pos, &b) in docs.iter().enumerate() {
+                let others = if docs.len() <= SMALL_BUCKET { &docs[..pos] } else { &reps[..] };
+                let mut joined = false;
+                for &a in others {
+                    let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                    if ra == rb {
+                        joined = true;
+                    } else if jaccard(signatures[a].as_ref().unwrap(), signatures[b].as_ref().unwrap()) >= threshold {
+                        parent[ra.max(rb)] = ra.min(rb);
+                        joined = true;
+                    }
+                }
+                if !joined {
+                    reps.push(b);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for doc in 0..signatures.len() {
+        let root = find(&mut parent, doc);
+        groups.entry(root).or_default().push(doc);
+    }
+
+    let mut clusters: Vec<Vec<(usize, f64)>> = groups.into_values()
+        .filter(|docs| docs.len() > 1)
+        .map(|docs| {
+            let first = signatures[docs[0]].as_ref().unwrap();
+            docs.iter().map(|&doc| (doc, jaccard(first, signatures[doc].as_ref().unwrap()))).collect()
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].0.cmp(&b[0].0)));
+    clusters
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a hash of words separated by spaces.
+fn fnv1a<'a, I: Iterator<Item = &'a str>>(words: I) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, word) in words.enumerate() {
+        let sep: &[u8] = if i > 0 { b" " } else { b"" };
+        for &byte in sep.iter().chain(word.as_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn join_documents_far_from_the_first_of_the_bucket() {
+        // the three share the first band, but only the last two are near-duplicates
+        let signatures = vec![Some(vec![1, 1, 5, 5]), Some(vec![1, 1, 2, 2]), Some(vec![1, 1, 2, 3])];
+        assert_eq!(clusters(&signatures, 2, 0.7), vec![vec![(1, 1.0), (2, 0.75)]]);
+    }
+
+    #[test]
+    fn join_documents_far_from_the_first_of_a_large_bucket() {
+        let mut signatures: Vec<Option<Vec<u32>>> = (0..2*SMALL_BUCKET as u32).map(|i| Some(vec![1, 1, 10 + i, 10 + i])).collect();
+        signatures.push(Some(vec![1, 1, 2, 2]));
+        signatures.push(Some(vec![1, 1, 2, 3]));
+        let n = signatures.len();
+        assert_eq!(clusters(&signatures, 2, 0.7), vec![vec![(n-2, 1.0), (n-1, 0.75)]]);
+    }
+}
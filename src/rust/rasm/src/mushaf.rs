@@ -0,0 +1,308 @@
+/*
+ *    mushaf.rs
+ *
+ * quran data structure for rasm: unique blocks and index sura[vers][word][block]
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ * quranic texts downloaded from https://tanzil.net/download
+ *   quran-simple.txt - no options selected
+ *   quran-uthmani.txt - Include pausal marks, Include sajdah signs (۩), Include rub-el-hizb signs (۞)
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::convert::Converter;
+
+/// Directory where the quran data structures are shipped.
+pub const DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../rasm_arch_data");
+
+/// Source of the quranic text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    TanzilSimple,
+    TanzilUthmani,
+    Decotype,
+}
+
+impl FromStr for Source {
+    type Err = ();
+    fn from_str(src: &str) -> std::result::Result<Source, ()> {
+        match src {
+            "tanzil-simple" => Ok(Source::TanzilSimple),
+            "tanzil-uthmani" => Ok(Source::TanzilUthmani),
+            "decotype" => Ok(Source::Decotype),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Source {
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Source::TanzilSimple => "mushaf_simple.json",
+            Source::TanzilUthmani => "mushaf_uthmani.json",
+            Source::Decotype => "mushaf_dt.json",
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        Path::new(DATA_DIR).join(self.file_name())
+    }
+}
+
+/// Quran data structure as stored in rasm_arch_data/mushaf_*.json.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Mushaf {
+    /// unique blocks: [(tok, pal), ... ]
+    pub tok: Vec<(String, String)>,
+    /// sura[vers][word][block] -> position in tok
+    pub ind: Vec<Vec<Vec<Vec<usize>>>>,
+}
+
+/// Word of the Quran with its index, as read from the source texts.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuranWord {
+    pub tok: String,
+    pub sura: usize,
+    pub vers: usize,
+    pub word: usize,
+}
+
+impl Mushaf {
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Mushaf> {
+        let fp = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(fp))?)
+    }
+
+    /// Load the structure shipped for source. The decotype quran is private and may be missing.
+    pub fn load_source(source: Source) -> Result<Mushaf> {
+        let path = source.path();
+        if source == Source::Decotype && !path.exists() {
+            return Err(Error::new(ErrorKind::NotFound, "Decotype Quran is private."));
+        }
+        Mushaf::load(path)
+    }
+
+    /// Iterate over all words with their index (sura, vers, word), starting in 1, and their blocks.
+    pub fn words(&self) -> impl Iterator<Item = ((usize, usize, usize), &[usize])> + '_ {
+        self.ind.iter().enumerate().flat_map(|(isura, sura)| {
+            sura.iter().enumerate().flat_map(move |(ivers, vers)| {
+                vers.iter().enumerate().map(move |(iword, word)| ((isura+1, ivers+1, iword+1), word.as_slice()))
+            })
+        })
+    }
+
+    /// Iterate over all blocks with their index (sura, vers, word, block), starting in 1, and their (tok, pal).
+    pub fn blocks(&self) -> impl Iterator<Item = ([usize; 4], &(String, String))> + '_ {
+        self.words().flat_map(move |((sura, vers, word), blocks)| {
+            blocks.iter().enumerate().map(move |(iblock, &itok)| ([sura, vers, word, iblock+1], &self.tok[itok]))
+        })
+    }
+
+    /// Write structure in the same json layout as Python's json.dump.
+    pub fn write_json<W: Write>(&self, out: W) -> Result<()> {
+        let mut out = BufWriter::new(out);
+
+        write!(out, "{{\"tok\": [")?;
+        for (i, (tok, pal)) in self.tok.iter().enumerate() {
+            if i > 0 { write!(out, ", ")? }
+            write!(out, "[{}, {}]", json_string(tok), json_string(pal))?;
+        }
+
+        write!(out, "], \"ind\": [")?;
+        for (isura, sura) in self.ind.iter().enumerate() {
+            if isura > 0 { write!(out, ", ")? }
+            write!(out, "[")?;
+            for (ivers, vers) in sura.iter().enumerate() {
+                if ivers > 0 { write!(out, ", ")? }
+                write!(out, "[")?;
+                for (iword, word) in vers.iter().enumerate() {
+                    if iword > 0 { write!(out, ", ")? }
+                    write!(out, "[{}]", word.iter().map(|b| b.to_string()).collect::<Vec<_>>().join(", "))?;
+                }
+                write!(out, "]")?;
+            }
+            write!(out, "]")?;
+        }
+        write!(out, "]}}")?;
+
+        out.flush()
+    }
+}
+
+/// Escape string as json with only ascii characters.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 6 + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut buf = [0u16; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    out.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Incremental construction of a mushaf structure, with unique blocks looked up in a hash map.
+#[derive(Debug, Default)]
+pub struct MushafBuilder {
+    mushaf: Mushaf,
+    seen: HashMap<(String, String), usize>,
+    pre: Option<(usize, usize, usize)>,
+}
+
+impl MushafBuilder {
+
+    pub fn new() -> MushafBuilder {
+        MushafBuilder::default()
+    }
+
+    /// Add block to the structure at index sura, vers, word.
+    pub fn push(&mut self, sura: usize, vers: usize, word: usize, tok: String, pal: String) {
+
+        let tok = (tok, pal);
+        let i = match self.seen.get(&tok) {
+            Some(&i) => i,
+            None => {
+                let i = self.mushaf.tok.len();
+                self.seen.insert(tok.clone(), i);
+                self.mushaf.tok.push(tok);
+                i
+            }
+        };
+
+        let ind = &mut self.mushaf.ind;
+        match self.pre {
+            Some((pre_sura, pre_vers, pre_word)) if pre_sura == sura => {
+                let sura_ind = ind.last_mut().unwrap();
+                if pre_vers != vers {
+                    sura_ind.push(vec![vec![i]]);
+                } else {
+                    let vers_ind = sura_ind.last_mut().unwrap();
+                    if pre_word != word {
+                        vers_ind.push(vec![i]);
+                    } else {
+                        vers_ind.last_mut().unwrap().push(i);
+                    }
+                }
+            }
+            _ => ind.push(vec![vec![vec![i]]]),
+        }
+
+        self.pre = Some((sura, vers, word));
+    }
+
+    /// Convert Quran word into letterblocks and add them to the structure.
+    pub fn push_word(&mut self, conv: &Converter, word: &QuranWord, unstable_alif: bool) {
+        for (tok, pal) in word_blocks(conv, &word.tok, unstable_alif) {
+            self.push(word.sura, word.vers, word.word, tok, pal);
+        }
+    }
+
+    pub fn finish(self) -> Mushaf {
+        self.mushaf
+    }
+}
+
+/// Convert Quran word into the (tok, pal) entries of its letterblocks.
+pub fn word_blocks(conv: &Converter, tok: &str, unstable_alif: bool) -> Vec<(String, String)> {
+
+    let groups = conv.paleo_blocks(tok, unstable_alif);
+
+    // ۞ ۩
+    if groups.first().is_none_or(|blocks| blocks.is_empty()) {
+        return vec![(tok.to_string(), String::new())];
+    }
+
+    groups.into_iter().flatten().map(|block| (block.ori, block.pal)).collect()
+}
+
+/// Build mushaf structure from sequence of Quran words.
+pub fn build<I: IntoIterator<Item = QuranWord>>(conv: &Converter, words: I, unstable_alif: bool) -> Mushaf {
+    let mut builder = MushafBuilder::new();
+    for word in words {
+        builder.push_word(conv, &word, unstable_alif);
+    }
+    builder.finish()
+}
+
+/// Read tanzil quran in pipe-delimited format sura|verse|text. Lines starting with # are comments.
+pub fn read_tanzil<R: BufRead>(fp: R) -> Result<Vec<QuranWord>> {
+
+    // attach pausal marks to the preceding word
+    let re_fix = Regex::new(" ([ۣۜۖۗۘۙۚۛ])").unwrap();
+
+    let mut words = Vec::new();
+
+    for (iline, line) in fp.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = || Error::new(ErrorKind::InvalidData, format!("line {}: expected sura|verse|text", iline+1));
+
+        let mut fields = line.splitn(3, '|');
+        let sura = fields.next().and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+        let vers = fields.next().and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+        let text = fields.next().ok_or_else(invalid)?;
+
+        let text = re_fix.replace_all(text, "$1");
+        words.extend(text.split_whitespace().enumerate().map(|(i, tok)| QuranWord {
+            tok: tok.to_string(),
+            sura,
+            vers,
+            word: i+1,
+        }));
+    }
+
+    Ok(words)
+}
+
+/// Read decotype quran, a json list of objects with keys tok, sura, vers and word.
+pub fn read_decotype<R: Read>(fp: R) -> Result<Vec<QuranWord>> {
+    Ok(serde_json::from_reader(fp)?)
+}
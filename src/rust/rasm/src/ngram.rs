@@ -0,0 +1,197 @@
+/*
+ *    ngram.rs
+ *
+ * frequencies of archigrapheme, letterblock and word n-grams of rasm
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::{HashMap, VecDeque};
+use std::io::prelude::*;
+use std::io::Result;
+use std::str::FromStr;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Unit of the n-grams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// archigraphemes inside a rasm word, regardless of block boundaries
+    Archigrapheme,
+    /// consecutive letterblocks
+    Block,
+    /// consecutive rasm words
+    Word,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+    fn from_str(src: &str) -> std::result::Result<Unit, String> {
+        match src {
+            "archigrapheme" | "char" => Ok(Unit::Archigrapheme),
+            "block" => Ok(Unit::Block),
+            "word" => Ok(Unit::Word),
+            _ => Err(format!("unknown n-gram unit \"{}\", expected archigrapheme, block or word", src)),
+        }
+    }
+}
+
+/// Streaming counter of rasm n-grams. Memory is bounded by capacity: when the table grows beyond it,
+/// the less frequent half of the n-grams is discarded, so the counts reported afterwards may fall short
+/// by at most error().
+#[derive(Debug)]
+pub struct NgramCounter {
+    unit: Unit,
+    n: usize,
+    capacity: usize,
+    window: VecDeque<String>,
+    counts: HashMap<String, u64>,
+    total: u64,
+    error: u64,
+}
+
+impl NgramCounter {
+
+    pub fn new(unit: Unit, n: usize, capacity: usize) -> NgramCounter {
+        NgramCounter {
+            unit,
+            n: n.max(1),
+            capacity: capacity.max(1),
+            window: VecDeque::new(),
+            counts: HashMap::new(),
+            total: 0,
+            error: 0,
+        }
+    }
+
+    /// Count the n-grams ending in a word given by its Latin rasm, blocks separated by spaces.
+    /// Block and word n-grams run across consecutive words until reset() is called.
+    pub fn add_word(&mut self, rasm: &str) {
+        match self.unit {
+            Unit::Archigrapheme => {
+                let arch: Vec<char> = rasm.chars().filter(|c| !c.is_whitespace()).collect();
+                for gram in arch.windows(self.n) {
+                    self.count(gram.iter().collect());
+                }
+            }
+            Unit::Block => {
+                for block in rasm.split_whitespace() {
+                    self.push(block.to_string());
+                }
+            }
+            Unit::Word => {
+                let word: String = rasm.split_whitespace().collect();
+                if !word.is_empty() {
+                    self.push(word);
+                }
+            }
+        }
+    }
+
+    /// Break the sequence of blocks or words, so that no n-gram spans the boundary.
+    pub fn reset(&mut self) {
+        self.window.clear();
+    }
+
+    /// Count the n-grams of all Arabic tokens of text, read line by line.
+    pub fn add_text<R: BufRead>(&mut self, conv: &Converter, text: R) -> Result<()> {
+        for line in text.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            for tok in line.split_whitespace() {
+                self.add_word(&conv.reduce(tok).lat);
+            }
+        }
+        self.reset();
+        Ok(())
+    }
+
+    /// Count the n-grams of the words of the mushaf, optionally only those inside range. N-grams do not
+    /// span the gaps left by the range.
+    pub fn add_mushaf(&mut self, conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>) {
+        for ((sura, vers, word), blocks) in quran.words() {
+            if range.is_some_and(|range| !range.contains(&[sura, vers, word])) {
+                self.reset();
+                continue;
+            }
+            let rasm = blocks.iter()
+                .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+                .filter(|b| !b.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.add_word(&rasm);
+        }
+        self.reset();
+    }
+
+    /// Number of n-grams counted, including those later discarded.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Maximum amount by which a reported count may fall short of the true one; 0 if the table never
+    /// exceeded its capacity and all counts are exact.
+    pub fn error(&self) -> u64 {
+        self.error
+    }
+
+    /// N-grams with their counts, most frequent first, then in alphabetical order.
+    pub fn sorted(&self) -> Vec<(&str, u64)> {
+        let mut grams: Vec<(&str, u64)> = self.counts.iter().map(|(gram, &n)| (gram.as_str(), n)).collect();
+        grams.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        grams
+    }
+
+    fn push(&mut self, item: String) {
+        self.window.push_back(item);
+        if self.window.len() > self.n {
+            self.window.pop_front();
+        }
+        if self.window.len() == self.n {
+            let gram = self.window.iter().map(String::as_str).collect::<Vec<_>>().join(" ");
+            self.count(gram);
+        }
+    }
+
+    fn count(&mut self, gram: String) {
+        self.total += 1;
+        *self.counts.entry(gram).or_insert(0) += 1;
+        if self.counts.len() > self.capacity {
+            self.prune();
+        }
+    }
+
+    /// Discard at least half of the table, from the least frequent n-grams up.
+    fn prune(&mut self) {
+        let mut counts: Vec<u64> = self.counts.values().copied().collect();
+        let mid = counts.len() / 2;
+        let (_, &mut threshold, _) = counts.select_nth_unstable(mid);
+        self.counts.retain(|_, n| *n > threshold);
+        // an n-gram loses at most threshold occurrences in each pruning
+        self.error += threshold;
+    }
+}
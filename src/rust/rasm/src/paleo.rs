@@ -0,0 +1,430 @@
+/*
+ *    paleo.rs
+ *
+ * parser of the paleo-orthographic notation into letters and their signs
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::inventory::ARCHIGRAPHEMES;
+
+/// Base of a letter of the notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base {
+    /// Latin archigrapheme
+    Arch(char),
+    /// hamza on the line, ʔ
+    Hamza,
+    /// tatweel, ـ, as a seat for a hamza or small letter
+    Tatweel,
+}
+
+/// Short vowel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vowel {
+    Fatha,
+    Damma,
+    Kasra,
+}
+
+/// Nunation of a short vowel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tanwin {
+    None,
+    /// ᵃⁿ ᵘⁿ ᵢₙ
+    Closed,
+    /// ᵃᵃ ᵘᵘ ᵢᵢ
+    Open,
+}
+
+/// Group of one to four consonantal dots, above or below the letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dots {
+    count: u8,
+    below: bool,
+}
+
+impl Dots {
+
+    /// Group of count dots; None unless count is between 1 and 4.
+    pub fn new(count: u8, below: bool) -> Option<Dots> {
+        (1..=4).contains(&count).then_some(Dots { count, below })
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    pub fn below(&self) -> bool {
+        self.below
+    }
+}
+
+/// Sign attached to a letter, in the order it is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// consonantal dots, eg. ² two above or ₁ one below
+    Dots(Dots),
+    /// one dot above in the older spelling ’, kept apart from ¹ so that the string is written back as it was
+    Apostrophe,
+    Vowel(Vowel, Tanwin),
+    /// ᵚ
+    Shadda,
+    /// ᵒ
+    Sukun,
+    /// ˀ above or ɂ below
+    Hamza { below: bool },
+    /// ˜
+    Madda,
+    /// ᵟ
+    Wasla,
+    /// ᴬ
+    DaggerAlif,
+    /// small letter: ᴺ ˢ ₛ ᵐ ₘ ʷ ʸ
+    Small(char),
+    /// quranic annotation: ° ⁰ ⌃ ⌄ •
+    Annotation(char),
+    /// pausal mark: ⒮ ⒬ ⒨ ⒧ ⒥ ∴
+    Pause(char),
+    /// other modification of the letter shape, like ᵀ or a digit ۲ ۳ ۴
+    Shape(char),
+}
+
+/// Letter of the notation with its signs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Letter {
+    pub base: Base,
+    pub marks: Vec<Mark>,
+}
+
+/// Paleo-orthographic string parsed into letterblocks of letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paleo {
+    pub blocks: Vec<Vec<Letter>>,
+}
+
+/// Malformed paleo-orthographic string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePaleoError {
+    pub pal: String,
+    /// position of the offending character, counted in characters from 1
+    pub position: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ParsePaleoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid paleo string \"{}\" at position {}: {}", self.pal, self.position, self.reason)
+    }
+}
+
+impl Error for ParsePaleoError {}
+
+const SMALL: &str = "ᴺˢₛᵐₘʷʸ";
+const ANNOTATIONS: &str = "°⁰⌃⌄•";
+const PAUSES: &str = "⒮⒬⒨⒧⒥∴";
+const SHAPES: &str = "ᵀₒᵛᶺ‸ᵥ₋тᐟᵠ।۲۳۴";
+
+impl Vowel {
+    fn sign(&self) -> char {
+        match self {
+            Vowel::Fatha => 'ᵃ',
+            Vowel::Damma => 'ᵘ',
+            Vowel::Kasra => 'ᵢ',
+        }
+    }
+
+    fn tanwin(&self) -> char {
+        match self {
+            Vowel::Kasra => 'ₙ',
+            _ => 'ⁿ',
+        }
+    }
+}
+
+impl Letter {
+
+    /// Archigrapheme of the letter, if it is not a hamza or tatweel.
+    pub fn arch(&self) -> Option<char> {
+        match self.base {
+            Base::Arch(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Groups of dots as (count, below).
+    pub fn dots(&self) -> impl Iterator<Item = (u8, bool)> + '_ {
+        self.marks.iter().filter_map(|m| match *m {
+            Mark::Dots(dots) => Some((dots.count, dots.below)),
+            Mark::Apostrophe => Some((1, false)),
+            _ => None,
+        })
+    }
+
+    /// Total number of dots.
+    pub fn dot_count(&self) -> usize {
+        self.dots().map(|(count, _)| count as usize).sum()
+    }
+
+    pub fn vowel(&self) -> Option<(Vowel, Tanwin)> {
+        self.marks.iter().find_map(|m| match *m {
+            Mark::Vowel(vowel, tanwin) => Some((vowel, tanwin)),
+            _ => None,
+        })
+    }
+
+    /// Hamza sign, true if below.
+    pub fn hamza(&self) -> Option<bool> {
+        self.marks.iter().find_map(|m| match *m {
+            Mark::Hamza { below } => Some(below),
+            _ => None,
+        })
+    }
+
+    pub fn pause(&self) -> Option<char> {
+        self.marks.iter().find_map(|m| match *m {
+            Mark::Pause(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    pub fn has(&self, mark: Mark) -> bool {
+        self.marks.contains(&mark)
+    }
+}
+
+impl fmt::Display for Mark {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mark::Dots(Dots { count, below }) => {
+                let digits = if below { "₁₂₃₄" } else { "¹²³⁴" };
+                write!(f, "{}", digits.chars().nth(count as usize - 1).unwrap())
+            }
+            Mark::Vowel(vowel, Tanwin::None) => write!(f, "{}", vowel.sign()),
+            Mark::Vowel(vowel, Tanwin::Closed) => write!(f, "{}{}", vowel.sign(), vowel.tanwin()),
+            Mark::Vowel(vowel, Tanwin::Open) => write!(f, "{}{}", vowel.sign(), vowel.sign()),
+            Mark::Apostrophe => write!(f, "’"),
+            Mark::Shadda => write!(f, "ᵚ"),
+            Mark::Sukun => write!(f, "ᵒ"),
+            Mark::Hamza { below } => write!(f, "{}", if below { 'ɂ' } else { 'ˀ' }),
+            Mark::Madda => write!(f, "˜"),
+            Mark::Wasla => write!(f, "ᵟ"),
+            Mark::DaggerAlif => write!(f, "ᴬ"),
+            Mark::Small(c) | Mark::Annotation(c) | Mark::Pause(c) | Mark::Shape(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+impl fmt::Display for Letter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.base {
+            Base::Arch(c) => write!(f, "{}", c)?,
+            Base::Hamza => write!(f, "ʔ")?,
+            Base::Tatweel => write!(f, "ـ")?,
+        }
+        self.marks.iter().try_for_each(|mark| write!(f, "{}", mark))
+    }
+}
+
+impl fmt::Display for Paleo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, block) in self.blocks.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            block.iter().try_for_each(|letter| write!(f, "{}", letter))?;
+        }
+        Ok(())
+    }
+}
+
+impl Paleo {
+
+    /// Letters of all blocks in order.
+    pub fn letters(&self) -> impl Iterator<Item = &Letter> {
+        self.blocks.iter().flatten()
+    }
+
+    /// Bare archigraphemes, blocks separated by spaces, as given by Converter::strip_paleo.
+    pub fn rasm(&self) -> String {
+        self.blocks.iter()
+            .map(|block| block.iter().filter_map(Letter::arch).collect::<String>())
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl FromStr for Paleo {
+    type Err = ParsePaleoError;
+
+    /// Parse a paleo-orthographic string. Letterblocks are separated by single spaces; every sign must follow
+    /// a letter, and tanwin must follow its vowel. The older spelling ’ of one dot above is accepted and
+    /// kept, so that Display gives back the string as parsed.
+    fn from_str(pal: &str) -> Result<Paleo, ParsePaleoError> {
+
+        let error = |position: usize, reason: String| ParsePaleoError { pal: pal.to_string(), position, reason };
+
+        let mut blocks: Vec<Vec<Letter>> = vec![vec![]];
+
+        for (i, c) in pal.chars().enumerate() {
+
+            let position = i+1;
+
+            let base = match c {
+                _ if ARCHIGRAPHEMES.contains(c) => Some(Base::Arch(c)),
+                'ʔ' => Some(Base::Hamza),
+                'ـ' => Some(Base::Tatweel),
+                _ => None,
+            };
+            if let Some(base) = base {
+                blocks.last_mut().unwrap().push(Letter { base, marks: vec![] });
+                continue;
+            }
+
+            if c == ' ' {
+                if blocks.last().unwrap().is_empty() {
+                    return Err(error(position, "empty letterblock".to_string()));
+                }
+                blocks.push(vec![]);
+                continue;
+            }
+
+            let letter = match blocks.last_mut().unwrap().last_mut() {
+                Some(letter) => letter,
+                None => return Err(error(position, format!("sign '{}' does not follow a letter", c))),
+            };
+
+            let mark = match c {
+                '’' => Mark::Apostrophe,
+                '¹' | '²' | '³' | '⁴' => Mark::Dots(Dots { count: "¹²³⁴".chars().position(|d| d == c).unwrap() as u8 + 1, below: false }),
+                '₁' | '₂' | '₃' | '₄' => Mark::Dots(Dots { count: "₁₂₃₄".chars().position(|d| d == c).unwrap() as u8 + 1, below: true }),
+                'ᵃ' | 'ᵘ' | 'ᵢ' => {
+                    let vowel = match c { 'ᵃ' => Vowel::Fatha, 'ᵘ' => Vowel::Damma, _ => Vowel::Kasra };
+                    if let Some(Mark::Vowel(last, tanwin @ Tanwin::None)) = letter.marks.last_mut() {
+                        if *last == vowel {
+                            *tanwin = Tanwin::Open;
+                            continue;
+                        }
+                    }
+                    Mark::Vowel(vowel, Tanwin::None)
+                }
+                'ⁿ' | 'ₙ' => {
+                    match letter.marks.last_mut() {
+                        Some(Mark::Vowel(vowel, tanwin @ Tanwin::None)) if vowel.tanwin() == c => {
+                            *tanwin = Tanwin::Closed;
+                            continue;
+                        }
+                        _ => return Err(error(position, format!("tanwin '{}' does not follow its vowel", c))),
+                    }
+                }
+                'ᵚ' => Mark::Shadda,
+                'ᵒ' => Mark::Sukun,
+                'ˀ' => Mark::Hamza { below: false },
+                'ɂ' => Mark::Hamza { below: true },
+                '˜' => Mark::Madda,
+                'ᵟ' => Mark::Wasla,
+                'ᴬ' => Mark::DaggerAlif,
+                _ if SMALL.contains(c) => Mark::Small(c),
+                _ if ANNOTATIONS.contains(c) => Mark::Annotation(c),
+                _ if PAUSES.contains(c) => Mark::Pause(c),
+                _ if SHAPES.contains(c) => Mark::Shape(c),
+                _ => return Err(error(position, format!("unknown character '{}'", c))),
+            };
+            letter.marks.push(mark);
+        }
+
+        if blocks.last().unwrap().is_empty() && !pal.is_empty() {
+            return Err(error(pal.chars().count(), "trailing space".to_string()));
+        }
+        if pal.is_empty() {
+            blocks.clear();
+        }
+
+        Ok(Paleo { blocks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::convert::Converter;
+    use crate::mushaf::{Mushaf, Source};
+
+    fn round_trip(source: Source) {
+        let conv = Converter::new();
+        let quran = Mushaf::load_source(source).unwrap();
+        for (_, pal) in &quran.tok {
+            let paleo: Paleo = pal.parse().unwrap();
+            assert_eq!(&paleo.to_string(), pal);
+            assert_eq!(paleo.rasm(), conv.strip_paleo(pal));
+        }
+    }
+
+    #[test]
+    fn round_trip_simple() {
+        round_trip(Source::TanzilSimple);
+    }
+
+    #[test]
+    fn round_trip_uthmani() {
+        round_trip(Source::TanzilUthmani);
+    }
+
+    #[test]
+    fn apostrophe_is_kept() {
+        for pal in ["B’ᵚᵃMᵃA˜", "Aᵟ LᵚᵃD’ᵢ Y"] {
+            let paleo: Paleo = pal.parse().unwrap();
+            assert_eq!(paleo.to_string(), pal);
+        }
+        let letter = &"B’ᵚᵃ".parse::<Paleo>().unwrap().blocks[0][0];
+        assert_eq!(letter.dots().collect::<Vec<_>>(), vec![(1, false)]);
+        assert_eq!(letter.dot_count(), 1);
+    }
+
+    #[test]
+    fn error_positions() {
+        let position = |pal: &str| pal.parse::<Paleo>().unwrap_err().position;
+        assert_eq!(position("ᵃB"), 1);
+        assert_eq!(position("Bᵃ  L"), 4);
+        assert_eq!(position("A Lⁿ"), 4);
+        assert_eq!(position("LᵃHᵘₙ"), 5);
+        assert_eq!(position("BSM "), 4);
+        assert_eq!(position("BXM"), 2);
+        assert_eq!(" B".parse::<Paleo>().unwrap_err().reason, "empty letterblock");
+    }
+
+    #[test]
+    fn dots_are_checked() {
+        assert_eq!(Dots::new(0, false), None);
+        assert_eq!(Dots::new(5, true), None);
+        let letter = Letter { base: Base::Arch('B'), marks: vec![Mark::Dots(Dots::new(3, false).unwrap())] };
+        assert_eq!(letter.to_string(), "B³");
+    }
+}
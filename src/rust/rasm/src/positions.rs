@@ -0,0 +1,136 @@
+/*
+ *    positions.rs
+ *
+ * frequencies of archigraphemes by position inside the letterblock
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::BTreeMap;
+use std::io::prelude::*;
+use std::io::Result;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Occurrences of an archigrapheme by position inside the letterblock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Counts {
+    /// block of a single archigrapheme
+    pub isolated: u64,
+    /// first of a block of two or more
+    pub initial: u64,
+    /// neither first nor last
+    pub medial: u64,
+    /// last of a block of two or more
+    #[serde(rename = "final")]
+    pub fin: u64,
+}
+
+impl Counts {
+
+    pub fn total(&self) -> u64 {
+        self.isolated + self.initial + self.medial + self.fin
+    }
+
+    fn merge(&mut self, other: &Counts) {
+        self.isolated += other.isolated;
+        self.initial += other.initial;
+        self.medial += other.medial;
+        self.fin += other.fin;
+    }
+}
+
+/// Positional frequencies of the archigraphemes of a corpus, serialised as archigrapheme -> counts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct PositionStats {
+    counts: BTreeMap<char, Counts>,
+}
+
+impl PositionStats {
+
+    pub fn new() -> PositionStats {
+        PositionStats::default()
+    }
+
+    /// Count the archigraphemes of a word given by its Latin rasm, blocks separated by spaces. Blocks
+    /// are closed by A R D W and by the word end, where N Q Y stand for the final forms.
+    pub fn add_word(&mut self, rasm: &str) {
+        for block in rasm.split_whitespace() {
+            let len = block.chars().count();
+            for (i, c) in block.chars().enumerate() {
+                let counts = self.counts.entry(c).or_default();
+                match (i, len) {
+                    (_, 1) => counts.isolated += 1,
+                    (0, _) => counts.initial += 1,
+                    _ if i == len-1 => counts.fin += 1,
+                    _ => counts.medial += 1,
+                }
+            }
+        }
+    }
+
+    /// Count the archigraphemes of all Arabic tokens of text.
+    pub fn add_text<R: BufRead>(&mut self, conv: &Converter, text: R) -> Result<()> {
+        for line in text.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            for tok in line.split_whitespace() {
+                self.add_word(&conv.reduce(tok).lat);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the counts of other.
+    pub fn merge(&mut self, other: &PositionStats) {
+        for (&c, counts) in &other.counts {
+            self.counts.entry(c).or_default().merge(counts);
+        }
+    }
+
+    /// Counts of each archigrapheme found, in alphabetical order.
+    pub fn rows(&self) -> impl Iterator<Item = (char, &Counts)> {
+        self.counts.iter().map(|(&c, counts)| (c, counts))
+    }
+}
+
+/// Positional frequencies of the archigraphemes of each sura of the mushaf, optionally only of the words
+/// inside range. Suras with no words in range are left out.
+pub fn sura_stats(conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>) -> BTreeMap<usize, PositionStats> {
+    let mut suras: BTreeMap<usize, PositionStats> = BTreeMap::new();
+    for ((sura, vers, word), blocks) in quran.words() {
+        if range.is_some_and(|range| !range.contains(&[sura, vers, word])) {
+            continue;
+        }
+        let stats = suras.entry(sura).or_default();
+        for &itok in blocks {
+            stats.add_word(&conv.strip_paleo(&quran.tok[itok].1));
+        }
+    }
+    suras
+}
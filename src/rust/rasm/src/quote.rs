@@ -0,0 +1,193 @@
+/*
+ *    quote.rs
+ *
+ * detection of quranic quotations in Arabic texts by rasm n-grams
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+
+use crate::align::block_distance;
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+
+/// Arabic token of an input text, with its position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextToken {
+    /// original token
+    pub ori: String,
+    /// archigraphemes in Latin script, without spaces
+    pub lat: String,
+    /// line number, starting in 1
+    pub line: usize,
+    /// byte offsets of the token in the text, end exclusive
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Passage of a text that matches a stretch of the Quran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quotation {
+    /// positions of the first and last tokens of the passage in the text
+    pub text: (usize, usize),
+    /// (sura, vers, word) of the first and last matched words of the Quran
+    pub quran: ([usize; 3], [usize; 3]),
+    /// number of words matched
+    pub words: usize,
+    /// number of matched words whose rasm differs
+    pub diffs: usize,
+}
+
+/// Split text into its Arabic tokens, i.e. runs of letters and diacritics, keeping their offsets.
+pub fn text_tokens(conv: &Converter, text: &str) -> Vec<TextToken> {
+
+    let mut toks = Vec::new();
+    let mut line = 1;
+    let mut start: Option<(usize, usize)> = None;
+
+    let is_arabic = |c: char| conv.is_letter(c) || conv.chars.diac.contains(c) || conv.chars.clusters.contains_key(c.to_string().as_str());
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), '\n'))) {
+        if is_arabic(c) {
+            start.get_or_insert((i, line));
+            continue;
+        }
+        if let Some((s, l)) = start.take() {
+            let ori = &text[s..i];
+            let lat: String = conv.reduce(&conv.normalise(ori)).lat.split_whitespace().collect();
+            if !lat.is_empty() {
+                toks.push(TextToken { ori: ori.to_string(), lat, line: l, start: s, end: i });
+            }
+        }
+        if c == '\n' {
+            line += 1;
+        }
+    }
+
+    toks
+}
+
+/// Index of the rasm n-grams of the words of a mushaf.
+#[derive(Debug)]
+pub struct QuoteIndex {
+    /// (sura, vers, word) and rasm without spaces of each word, in order
+    words: Vec<([usize; 3], String)>,
+    /// rasm n-gram, words joined by spaces -> positions in words where it starts
+    ngrams: HashMap<String, Vec<usize>>,
+    n: usize,
+}
+
+impl QuoteIndex {
+
+    /// Index all n-grams of words of the mushaf. Words with no archigraphemes (۞ ۩) are left out.
+    pub fn build(conv: &Converter, quran: &Mushaf, n: usize) -> QuoteIndex {
+
+        let n = n.max(1);
+
+        let words: Vec<([usize; 3], String)> = quran.words()
+            .map(|((sura, vers, word), blocks)| {
+                ([sura, vers, word], blocks.iter().map(|&itok| conv.strip_paleo(&quran.tok[itok].1)).collect::<String>())
+            })
+            .filter(|(_, lat)| !lat.is_empty())
+            .collect();
+
+        let mut ngrams: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, window) in words.windows(n).enumerate() {
+            ngrams.entry(ngram(window.iter().map(|(_, lat)| lat.as_str()))).or_default().push(i);
+        }
+
+        QuoteIndex { words, ngrams, n }
+    }
+
+    /// Find passages of at least min_words words that match the Quran. Matches start from an exact n-gram
+    /// and are extended in both directions while the rasm of the words differs by at most tolerance,
+    /// measured as edit distance over archigraphemes divided by the length of the longest word.
+    pub fn find(&self, toks: &[TextToken], min_words: usize, tolerance: f64) -> Vec<Quotation> {
+
+        let similar = |i: usize, j: usize| block_distance(&toks[i].lat, &self.words[j].1) <= tolerance;
+
+        // diagonal (quran position - text position) -> text position up to which it is already covered
+        let mut covered: HashMap<isize, usize> = HashMap::new();
+        let mut found = Vec::new();
+
+        for (i, window) in toks.windows(self.n).enumerate() {
+
+            let key = ngram(window.iter().map(|t| t.lat.as_str()));
+            let starts = match self.ngrams.get(&key) {
+                Some(starts) => starts,
+                None => continue,
+            };
+
+            for &j in starts {
+
+                let diag = j as isize - i as isize;
+                if covered.get(&diag).is_some_and(|&end| i <= end) {
+                    continue;
+                }
+
+                // extend backwards and forwards
+                let (mut ti, mut qi) = (i, j);
+                while ti > 0 && qi > 0 && similar(ti-1, qi-1) {
+                    ti -= 1;
+                    qi -= 1;
+                }
+                let (mut tj, mut qj) = (i + self.n - 1, j + self.n - 1);
+                while tj+1 < toks.len() && qj+1 < self.words.len() && similar(tj+1, qj+1) {
+                    tj += 1;
+                    qj += 1;
+                }
+
+                // do not begin or end with a differing word
+                while toks[ti].lat != self.words[qi].1 {
+                    ti += 1;
+                    qi += 1;
+                }
+                while toks[tj].lat != self.words[qj].1 {
+                    tj -= 1;
+                    qj -= 1;
+                }
+
+                covered.insert(diag, tj);
+
+                let words = tj - ti + 1;
+                if words < min_words {
+                    continue;
+                }
+                let diffs = (ti..=tj).zip(qi..=qj).filter(|&(t, q)| toks[t].lat != self.words[q].1).count();
+
+                found.push(Quotation { text: (ti, tj), quran: (self.words[qi].0, self.words[qj].0), words, diffs });
+            }
+        }
+
+        // formulae such as "la ilaha illa huwa" occur in many places: keep them only when they are
+        // not part of a longer quotation
+        let longer = |q: &Quotation| found.iter().any(|p| p.words > q.words && p.text.0 <= q.text.0 && q.text.1 <= p.text.1);
+        found.iter().filter(|q| !longer(q)).cloned().collect()
+    }
+}
+
+fn ngram<'a, I: Iterator<Item = &'a str>>(words: I) -> String {
+    words.collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,369 @@
+/*
+ *    range.rs
+ *
+ * quranic index ranges: i[:j[:k[:m]]][-n[:p[:q[:r]]]], sura names, divisions and unions of them
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::quran::{Division, SURAS, VERSES, format_index, sura_by_name};
+
+/// Position in the Quran. Indexes start in 1; verse, word and block may be absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub sura: usize,
+    pub vers: Option<usize>,
+    pub word: Option<usize>,
+    pub block: Option<usize>,
+}
+
+/// Contiguous span of the Quran. Both limits are inclusive; if end is absent, it is the same as ini.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub ini: Position,
+    pub end: Option<Position>,
+}
+
+/// Union of spans of the Quran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuranRange {
+    pub spans: Vec<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRangeError {
+    pub arg: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid quran range \"{}\": {}", self.arg, self.reason)
+    }
+}
+
+impl Error for ParseRangeError {}
+
+impl Position {
+
+    pub fn verse(sura: usize, vers: usize) -> Position {
+        Position { sura, vers: Some(vers), word: None, block: None }
+    }
+
+    /// Defined indexes, from sura down to the most specific one.
+    fn parts(&self) -> Vec<usize> {
+        [Some(self.sura), self.vers, self.word, self.block].iter().map_while(|&i| i).collect()
+    }
+
+    fn from_parts(parts: &[usize]) -> Position {
+        Position {
+            sura: parts[0],
+            vers: parts.get(1).copied(),
+            word: parts.get(2).copied(),
+            block: parts.get(3).copied(),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_index(&self.parts()))
+    }
+}
+
+impl Span {
+
+    /// Check if index (sura, vers, word, block) falls inside the span. Only as many levels
+    /// of the index as given in each limit are compared.
+    pub fn contains(&self, index: &[usize]) -> bool {
+        let ini = self.ini.parts();
+        let end = self.end.unwrap_or(self.ini).parts();
+        let (i, j) = (ini.len().min(index.len()), end.len().min(index.len()));
+        ini[..i] <= index[..i] && index[..j] <= end[..j]
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end {
+            Some(end) if end != self.ini => write!(f, "{}-{}", self.ini, end),
+            _ => write!(f, "{}", self.ini),
+        }
+    }
+}
+
+impl QuranRange {
+
+    /// The whole Quran.
+    pub fn all() -> QuranRange {
+        QuranRange { spans: vec![Span { ini: Position::from_parts(&[1]), end: Some(Position::from_parts(&[SURAS])) }] }
+    }
+
+    /// Check if index (sura, vers, word, block), or a prefix of it, falls inside any of the spans.
+    pub fn contains(&self, index: &[usize]) -> bool {
+        self.spans.iter().any(|span| span.contains(index))
+    }
+}
+
+impl fmt::Display for QuranRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.spans.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(","))
+    }
+}
+
+impl FromStr for QuranRange {
+    type Err = ParseRangeError;
+
+    /// Parse comma-separated union of ranges. Digits may be ASCII, Arabic-Indic or Extended Arabic-Indic,
+    /// and the Arabic comma and semicolon may separate the ranges. Each range is one of:
+    ///
+    ///   all
+    ///   i[:j[:k[:m]]][-n[:p[:q[:r]]]]   sura may be given by its name, e.g. al-Baqara:255
+    ///   juz|hizb|manzil|page N[-M]      page refers to the Madina mushaf
+    fn from_str(arg: &str) -> Result<QuranRange, ParseRangeError> {
+        let error = |reason: String| ParseRangeError { arg: arg.to_string(), reason };
+
+        let spans = normalise_arg(arg).split(',')
+            .map(|item| parse_span(item.trim()).map_err(error))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(QuranRange { spans })
+    }
+}
+
+/// Convert Arabic-Indic (U+0660–0669) and Extended Arabic-Indic (U+06F0–06F9) digits into ASCII,
+/// Arabic comma and semicolon into comma and dashes into hyphen, and drop bidi marks copied along
+/// with Arabic text.
+fn normalise_arg(arg: &str) -> String {
+    arg.chars()
+        .filter(|c| !matches!(c, '\u{200e}' | '\u{200f}' | '\u{061c}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'))
+        .map(|c| match c {
+            '\u{0660}'..='\u{0669}' => char::from(b'0' + (c as u32 - 0x0660) as u8),
+            '\u{06f0}'..='\u{06f9}' => char::from(b'0' + (c as u32 - 0x06f0) as u8),
+            '،' | '؛' => ',',
+            '‐' | '‑' | '–' | '—' => '-',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Parse one item of a union.
+fn parse_span(item: &str) -> Result<Span, String> {
+
+    if item == "all" {
+        return Ok(QuranRange::all().spans[0]);
+    }
+
+    if let Some(span) = parse_division(item)? {
+        return Ok(span);
+    }
+
+    // sura names may contain hyphens, so try every hyphen as the separator of the limits
+    let mut candidates = vec![(item, None)];
+    candidates.extend(item.match_indices('-').map(|(i, _)| (&item[..i], Some(&item[i+1..]))));
+
+    let mut reason = format!("expected all|juz|hizb|manzil|page N or i[:j[:k[:m]]][-n[:p[:q[:r]]]], got \"{}\"", item);
+    for (ini, end) in candidates {
+        // a hyphen right after an unknown name belongs to the name, as in al-Foo, and does not end the span
+        let head = ini.split(':').next().unwrap_or("").trim();
+        if end.is_some() && head.starts_with(|c: char| c.is_alphabetic()) && sura_by_name(head).is_none() {
+            continue;
+        }
+        let span = parse_position(ini).and_then(|ini| {
+            let end = end.map(parse_position).transpose()?;
+            check_span(Span { ini, end })
+        });
+        match span {
+            Ok(span) => return Ok(span),
+            // keep the most specific explanation
+            Err(Some(why)) => reason = why,
+            Err(None) => {},
+        }
+    }
+    Err(reason)
+}
+
+/// Parse "juz 30", "hizb 5-6", "page 106", etc. Return None if item does not name a division.
+fn parse_division(item: &str) -> Result<Option<Span>, String> {
+
+    let name_len = item.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(item.len());
+    let division = match item[..name_len].to_lowercase().parse::<Division>() {
+        Ok(division) => division,
+        Err(()) => return Ok(None),
+    };
+
+    let nums = item[name_len..].trim_start_matches(|c: char| c.is_whitespace() || c == ':');
+    let (ini, end) = match nums.split_once('-') {
+        Some((ini, end)) => (ini.trim(), end.trim()),
+        None => (nums.trim(), nums.trim()),
+    };
+
+    let unit = |num: &str| {
+        parse_number(num)
+            .and_then(|n| division.bounds(n))
+            .ok_or_else(|| format!("{} must be a number between 1 and {}", division.name(), division.starts().len()))
+    };
+    let ((ini_sura, ini_vers), _) = unit(ini)?;
+    let (_, (end_sura, end_vers)) = unit(end)?;
+
+    check_span(Span { ini: Position::verse(ini_sura, ini_vers), end: Some(Position::verse(end_sura, end_vers)) })
+        .map(Some)
+        .map_err(|why| why.unwrap_or_default())
+}
+
+/// Parse sura[:vers[:word[:block]]], where sura is a number or a sura name.
+/// Errors are None when the text does not look like a position at all.
+fn parse_position(text: &str) -> Result<Position, Option<String>> {
+
+    let mut fields = text.split(':');
+    let head = fields.next().unwrap_or("").trim();
+
+    let sura = match parse_number(head) {
+        Some(sura) => sura,
+        None if head.starts_with(|c: char| c.is_alphabetic()) => match sura_by_name(head) {
+            Some(sura) => sura,
+            None => return Err(Some(format!("unknown sura name \"{}\"", head))),
+        },
+        None => return Err(None),
+    };
+
+    let mut parts = vec![sura];
+    for field in fields {
+        if parts.len() == 4 {
+            return Err(None);
+        }
+        parts.push(parse_number(field.trim()).ok_or(None)?);
+    }
+
+    Ok(Position::from_parts(&parts))
+}
+
+/// Number of 1 to 3 digits, not starting with 0.
+fn parse_number(text: &str) -> Option<usize> {
+    if text.is_empty() || text.len() > 3 || text.starts_with('0') || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Check that suras and verses exist and that the span is not reversed.
+fn check_span(span: Span) -> Result<Span, Option<String>> {
+    for pos in Some(span.ini).iter().chain(span.end.iter()) {
+        if pos.sura > SURAS {
+            return Err(Some(format!("sura {} out of range, the Quran has {} suras", pos.sura, SURAS)));
+        }
+        if let Some(vers) = pos.vers {
+            if vers > VERSES[pos.sura-1] {
+                return Err(Some(format!("verse {} out of range, sura {} has {} verses", vers, pos.sura, VERSES[pos.sura-1])));
+            }
+        }
+    }
+    if let Some(end) = span.end {
+        let (ini, end) = (span.ini.parts(), end.parts());
+        let len = ini.len().min(end.len());
+        if ini[..len] > end[..len] {
+            return Err(Some(format!("start {} is after end {}", span.ini, span.end.unwrap())));
+        }
+    }
+    Ok(span)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn parsed(arg: &str) -> String {
+        arg.parse::<QuranRange>().unwrap().to_string()
+    }
+
+    fn reason(arg: &str) -> String {
+        arg.parse::<QuranRange>().unwrap_err().reason
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(parsed("all"), "1-114");
+        assert_eq!(parsed("2"), "2");
+        assert_eq!(parsed("2:255"), "2:255");
+        assert_eq!(parsed("2:255:3:1-2:256"), "2:255:3:1-2:256");
+        assert_eq!(parsed("2:255 – 2:257"), "2:255-2:257");
+    }
+
+    #[test]
+    fn divisions() {
+        assert_eq!(parsed("juz 30"), "78:1-114:6");
+        assert_eq!(parsed("juz 1"), "1:1-2:141");
+        assert_eq!(parsed("hizb 5-6"), "2:253-3:92");
+        assert_eq!(parsed("manzil 7"), "50:1-114:6");
+        assert_eq!(parsed("page 106"), "4:176-5:2");
+        assert_eq!(parsed("page 604"), "112:1-114:6");
+    }
+
+    #[test]
+    fn sura_names() {
+        assert_eq!(parsed("al-Baqara:255"), "2:255");
+        assert_eq!(parsed("al-Baqara-al-Imran"), "2-3");
+        assert_eq!(parsed("Taa-Haa:1-Taa-Haa:3"), "20:1-20:3");
+    }
+
+    #[test]
+    fn arabic_digits_and_punctuation() {
+        assert_eq!(parsed("٢:٢٥٥"), "2:255");
+        assert_eq!(parsed("۲:۲۵۵"), "2:255");
+        assert_eq!(parsed("\u{200f}٢:٢٥٥-٢:٢٥٧"), "2:255-2:257");
+        assert_eq!(parsed("١٨،٣٦:٢"), "18,36:2");
+        assert_eq!(parsed("2:255؛ juz 30"), "2:255,78:1-114:6");
+    }
+
+    #[test]
+    fn unions() {
+        let range: QuranRange = "al-Fatiha,2:255,juz 30".parse().unwrap();
+        assert_eq!(range.to_string(), "1,2:255,78:1-114:6");
+        assert!(range.contains(&[1, 7, 2]));
+        assert!(range.contains(&[2, 255, 1]));
+        assert!(!range.contains(&[2, 256, 1]));
+        assert!(range.contains(&[114, 6]));
+        assert!(!range.contains(&[77, 50]));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(reason("juz 0"), "juz must be a number between 1 and 30");
+        assert_eq!(reason("hizb 61"), "hizb must be a number between 1 and 60");
+        assert_eq!(reason("page 605"), "page must be a number between 1 and 604");
+        assert_eq!(reason("115"), "sura 115 out of range, the Quran has 114 suras");
+        assert_eq!(reason("2:287"), "verse 287 out of range, sura 2 has 286 verses");
+        assert_eq!(reason("3:1-2"), "start 3:1 is after end 2");
+        assert_eq!(reason("al-Foo"), "unknown sura name \"al-Foo\"");
+        assert_eq!(reason("Baqara-Fooo"), "unknown sura name \"Fooo\"");
+        assert_eq!(reason("2:0"), "expected all|juz|hizb|manzil|page N or i[:j[:k[:m]]][-n[:p[:q[:r]]]], got \"2:0\"");
+        assert_eq!(reason("2:1:2:3:4"), "expected all|juz|hizb|manzil|page N or i[:j[:k[:m]]][-n[:p[:q[:r]]]], got \"2:1:2:3:4\"");
+        assert_eq!(reason("2:255,"), "expected all|juz|hizb|manzil|page N or i[:j[:k[:m]]][-n[:p[:q[:r]]]], got \"\"");
+    }
+}
@@ -0,0 +1,201 @@
+/*
+ *    restore.rs
+ *
+ * statistical restoration of dots: bigram model over dotted tokens decoded from rasm input
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::io::Result;
+
+use crate::convert::Converter;
+use crate::dotting::query_rasm;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Start of sentence, used as history of the first token.
+const START: &str = "<s>";
+
+/// Weight of the bigram estimate against the unigram one.
+const LAMBDA: f64 = 0.7;
+
+/// Dotted reading proposed for a rasm word.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restored {
+    /// Latin rasm of the input word, without spaces
+    pub rasm: String,
+    /// most probable dotted form, None if the rasm was never seen in training
+    pub best: Option<String>,
+    /// probability of best given the whole sentence
+    pub confidence: f64,
+    /// other dotted forms and their probabilities, most probable first
+    pub alternatives: Vec<(String, f64)>,
+}
+
+/// Interpolated bigram model over dotted consonantal tokens, with the tokens indexed by their rasm.
+#[derive(Debug, Default)]
+pub struct DottingModel {
+    unigrams: HashMap<String, usize>,
+    bigrams: HashMap<(String, String), usize>,
+    /// number of times each token is followed by another one
+    histories: HashMap<String, usize>,
+    /// rasm without spaces -> dotted forms
+    by_rasm: HashMap<String, Vec<String>>,
+    total: usize,
+}
+
+impl DottingModel {
+
+    pub fn new() -> DottingModel {
+        DottingModel::default()
+    }
+
+    /// Add sentence of Arabic tokens to the model. Diacritics are removed, so only dotted consonants are modelled.
+    pub fn train_sentence<'a, I: IntoIterator<Item = &'a str>>(&mut self, conv: &Converter, toks: I) {
+        let mut prev = START.to_string();
+        for tok in toks {
+            let rasm: String = conv.reduce(tok).lat.split_whitespace().collect();
+            if rasm.is_empty() {
+                continue;
+            }
+            let dotted = conv.re_clean.replace_all(tok, "").into_owned();
+
+            let count = self.unigrams.entry(dotted.clone()).or_insert(0);
+            if *count == 0 {
+                self.by_rasm.entry(rasm).or_default().push(dotted.clone());
+            }
+            *count += 1;
+            self.total += 1;
+
+            *self.histories.entry(prev.clone()).or_insert(0) += 1;
+            *self.bigrams.entry((prev, dotted.clone())).or_insert(0) += 1;
+            prev = dotted;
+        }
+    }
+
+    /// Train on dotted text, each line taken as a sentence.
+    pub fn train_text<R: BufRead>(&mut self, conv: &Converter, text: R) -> Result<()> {
+        for line in text.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            self.train_sentence(conv, line.split_whitespace());
+        }
+        Ok(())
+    }
+
+    /// Train on the tokens of the mushaf, optionally only inside range, each verse taken as a sentence.
+    pub fn train_mushaf(&mut self, conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>) {
+        for (isura, sura) in quran.ind.iter().enumerate() {
+            for (ivers, vers) in sura.iter().enumerate() {
+                let words: Vec<String> = vers.iter().enumerate()
+                    .filter(|&(iword, _)| range.is_none_or(|range| range.contains(&[isura+1, ivers+1, iword+1])))
+                    .map(|(_, blocks)| blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect())
+                    .collect();
+                self.train_sentence(conv, words.iter().map(|w| w.as_str()));
+            }
+        }
+    }
+
+    /// Dotted forms seen in training for Latin rasm without spaces.
+    pub fn forms(&self, rasm: &str) -> &[String] {
+        self.by_rasm.get(rasm).map_or(&[], |forms| forms.as_slice())
+    }
+
+    fn prob(&self, prev: &str, tok: &str) -> f64 {
+        let uni = (self.unigrams.get(tok).copied().unwrap_or(0) + 1) as f64 / (self.total + self.unigrams.len() + 1) as f64;
+        let bi = match self.histories.get(prev) {
+            Some(&n) => self.bigrams.get(&(prev.to_string(), tok.to_string())).copied().unwrap_or(0) as f64 / n as f64,
+            None => 0.0,
+        };
+        LAMBDA * bi + (1.0 - LAMBDA) * uni
+    }
+
+    /// Propose dotted readings for a sentence of rasm words, in Latin archigraphemes (one word per token,
+    /// without spaces between blocks) or in Arabic script. Each word gets the form with the highest
+    /// probability given the whole sentence, computed with the forward-backward algorithm.
+    pub fn restore<'a, I: IntoIterator<Item = &'a str>>(&self, conv: &Converter, words: I) -> Vec<Restored> {
+
+        let rasms: Vec<String> = words.into_iter().map(|w| query_rasm(conv, w)).collect();
+
+        // unseen words get no candidates and break the chain, as if a new sentence started after them
+        let cands: Vec<&[String]> = rasms.iter().map(|r| self.forms(r)).collect();
+
+        let start = [START.to_string()];
+        let prevs = |i: usize| if i == 0 || cands[i-1].is_empty() { &start[..] } else { cands[i-1] };
+        let trans = |i: usize, j: usize, k: usize| {
+            self.prob(&prevs(i)[j], &cands[i][k])
+        };
+
+        // forward, normalised at each step
+        let mut alpha: Vec<Vec<f64>> = Vec::with_capacity(cands.len());
+        for i in 0..cands.len() {
+            let prev_alpha = if i == 0 || cands[i-1].is_empty() { vec![1.0] } else { alpha[i-1].clone() };
+            let mut a: Vec<f64> = (0..cands[i].len())
+                .map(|k| prev_alpha.iter().enumerate().map(|(j, pa)| pa * trans(i, j, k)).sum())
+                .collect();
+            normalise(&mut a);
+            alpha.push(a);
+        }
+
+        // backward, normalised at each step
+        let mut beta: Vec<Vec<f64>> = cands.iter().map(|c| vec![1.0; c.len()]).collect();
+        for i in (0..cands.len().saturating_sub(1)).rev() {
+            if cands[i].is_empty() || cands[i+1].is_empty() {
+                continue;
+            }
+            let mut b: Vec<f64> = (0..cands[i].len())
+                .map(|j| (0..cands[i+1].len()).map(|k| trans(i+1, j, k) * beta[i+1][k]).sum())
+                .collect();
+            normalise(&mut b);
+            beta[i] = b;
+        }
+
+        rasms.into_iter().enumerate().map(|(i, rasm)| {
+            let mut post: Vec<(String, f64)> = cands[i].iter().enumerate()
+                .map(|(k, cand)| (cand.clone(), alpha[i][k] * beta[i][k]))
+                .collect();
+            let total: f64 = post.iter().map(|(_, p)| p).sum();
+            if total > 0.0 {
+                post.iter_mut().for_each(|(_, p)| *p /= total);
+            }
+            post.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+            let mut post = post.into_iter();
+            match post.next() {
+                Some((best, confidence)) => Restored { rasm, best: Some(best), confidence, alternatives: post.collect() },
+                None => Restored { rasm, best: None, confidence: 0.0, alternatives: Vec::new() },
+            }
+        }).collect()
+    }
+}
+
+fn normalise(probs: &mut [f64]) {
+    let total: f64 = probs.iter().sum();
+    if total > 0.0 {
+        probs.iter_mut().for_each(|p| *p /= total);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ *    rhyme.rs
+ *
+ * verse-final rhyme (fasila) of the Quran by rasm
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::BTreeMap;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Rhyme of a verse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ending {
+    pub sura: usize,
+    pub vers: usize,
+    /// last letterblock or last archigraphemes of the verse, in Latin script
+    pub rhyme: String,
+}
+
+/// Consecutive verses of a sura sharing the same rhyme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub sura: usize,
+    /// first and last verse of the run
+    pub first: usize,
+    pub last: usize,
+    pub rhyme: String,
+}
+
+impl Run {
+    /// Number of verses of the run.
+    pub fn verses(&self) -> usize {
+        self.last - self.first + 1
+    }
+}
+
+/// Rhyme of each verse of the mushaf, optionally only of the verses inside range. The rhyme is the last
+/// letterblock of the verse if n is 0, otherwise its last n archigraphemes regardless of blocks and words.
+/// Signs with no archigraphemes at the end of the verse (۩) are skipped.
+pub fn endings(conv: &Converter, quran: &Mushaf, n: usize, range: Option<&QuranRange>) -> Vec<Ending> {
+
+    let mut endings = Vec::new();
+
+    for (isura, verses) in quran.ind.iter().enumerate() {
+        for (ivers, words) in verses.iter().enumerate() {
+
+            let (sura, vers) = (isura+1, ivers+1);
+            if range.is_some_and(|range| !range.contains(&[sura, vers])) {
+                continue;
+            }
+
+            let blocks: Vec<_> = words.iter()
+                .flatten()
+                .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+                .filter(|b| !b.trim().is_empty())
+                .collect();
+
+            let rhyme = if n == 0 {
+                blocks.last().map(|b| b.trim().to_string()).unwrap_or_default()
+            } else {
+                let arch: Vec<char> = blocks.iter().flat_map(|b| b.chars()).filter(|c| !c.is_whitespace()).collect();
+                arch[arch.len().saturating_sub(n)..].iter().collect()
+            };
+
+            endings.push(Ending { sura, vers, rhyme });
+        }
+    }
+
+    endings
+}
+
+/// Split endings into runs of consecutive verses of the same sura with the same rhyme. Every run but the first
+/// of a sura marks a change of rhyme.
+pub fn runs(endings: &[Ending]) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for ending in endings {
+        match runs.last_mut() {
+            Some(run) if run.sura == ending.sura && run.last+1 == ending.vers && run.rhyme == ending.rhyme => {
+                run.last = ending.vers;
+            }
+            _ => runs.push(Run { sura: ending.sura, first: ending.vers, last: ending.vers, rhyme: ending.rhyme.clone() }),
+        }
+    }
+    runs
+}
+
+/// Group the verses of each sura by rhyme: sura -> rhyme -> verses. Rhymes are in order of first appearance
+/// within the sura.
+pub fn groups(endings: &[Ending]) -> BTreeMap<usize, Vec<(String, Vec<usize>)>> {
+    let mut suras: BTreeMap<usize, Vec<(String, Vec<usize>)>> = BTreeMap::new();
+    for ending in endings {
+        let groups = suras.entry(ending.sura).or_default();
+        match groups.iter_mut().find(|(rhyme, _)| *rhyme == ending.rhyme) {
+            Some((_, verses)) => verses.push(ending.vers),
+            None => groups.push((ending.rhyme.clone(), vec![ending.vers])),
+        }
+    }
+    suras
+}
+
+/// Verse where the rhyme patterns of two sources differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub sura: usize,
+    pub vers: usize,
+    pub rhyme_a: String,
+    pub rhyme_b: String,
+    /// whether a change of rhyme starts at the verse in each source
+    pub change_a: bool,
+    pub change_b: bool,
+}
+
+/// Compare the endings of two sources with the same verse division, verse by verse. Verses are reported if
+/// their rhymes differ or if the rhyme changes there in only one of the sources.
+pub fn compare(a: &[Ending], b: &[Ending]) -> Vec<Divergence> {
+
+    let changes = |endings: &[Ending]| -> Vec<bool> {
+        endings.iter().enumerate()
+            .map(|(i, e)| i > 0 && endings[i-1].sura == e.sura && endings[i-1].rhyme != e.rhyme)
+            .collect()
+    };
+    let (changes_a, changes_b) = (changes(a), changes(b));
+
+    a.iter().zip(b).enumerate()
+        .filter(|&(i, (ea, eb))| ea.rhyme != eb.rhyme || changes_a[i] != changes_b[i])
+        .map(|(i, (ea, eb))| Divergence {
+            sura: ea.sura,
+            vers: ea.vers,
+            rhyme_a: ea.rhyme.clone(),
+            rhyme_b: eb.rhyme.clone(),
+            change_a: changes_a[i],
+            change_b: changes_b[i],
+        })
+        .collect()
+}
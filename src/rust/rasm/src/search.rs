@@ -0,0 +1,169 @@
+/*
+ *    search.rs
+ *
+ * search of the Quran by rasm: Arabic words reduced to archigraphemes or Latin patterns with wildcards
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::error::Error;
+use std::fmt;
+
+use regex::Regex;
+
+use crate::convert::Converter;
+use crate::index::RasmIndex;
+use crate::inventory::{ARCHIGRAPHEMES, FINAL_ONLY};
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Compiled rasm pattern, matched against whole words.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    re: Regex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pub pattern: String,
+    pub reason: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rasm pattern \"{}\": {}", self.pattern, self.reason)
+    }
+}
+
+impl Error for PatternError {}
+
+/// Word of the Quran matching a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// (sura, vers, word), starting in 1
+    pub index: [usize; 3],
+    /// original dotted form
+    pub tok: String,
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub lat: String,
+    /// paleo-orthographic representation, blocks separated by spaces
+    pub pal: String,
+}
+
+impl Pattern {
+
+    /// Compile query into a pattern. A query in Arabic script is reduced into archigraphemes and
+    /// matched literally. A query in Latin archigraphemes may contain:
+    ///
+    /// - `?` any archigrapheme
+    /// - `*` any sequence of archigraphemes, also empty
+    /// - `[..]` one of the archigraphemes in brackets, `[^..]` none of them
+    ///
+    /// Spaces between blocks are ignored, eg. B?LM* or "A L[BN]*". The final-only archigraphemes Q, N
+    /// and Y may only be followed by `*`, as no word continues after them.
+    pub fn new(conv: &Converter, query: &str) -> Result<Pattern, PatternError> {
+
+        let error = |reason: String| PatternError { pattern: query.to_string(), reason };
+
+        if !query.is_ascii() {
+            let rasm: String = conv.reduce(&conv.normalise(query)).lat.split_whitespace().collect();
+            if rasm.is_empty() {
+                return Err(error("no Arabic letters found".to_string()));
+            }
+            return Ok(Pattern { re: Regex::new(&format!("^{}$", rasm)).unwrap() });
+        }
+
+        let mut re = String::from("^");
+        let mut in_class = false;
+        let mut final_letter: Option<(char, usize)> = None;
+
+        for (i, c) in query.chars().enumerate() {
+            if let Some((f, pos)) = final_letter {
+                if !in_class && !c.is_whitespace() && c != '*' {
+                    return Err(error(format!("{} at position {} can only appear at the end of a word", f, pos)));
+                }
+            }
+            match c {
+                _ if c.is_whitespace() => {},
+                '[' if !in_class => { in_class = true; re.push('[') },
+                ']' if in_class => {
+                    if re.ends_with('[') || re.ends_with("[^") {
+                        return Err(error(format!("empty character class at position {}", i+1)));
+                    }
+                    in_class = false;
+                    re.push(']');
+                }
+                '^' if in_class && re.ends_with('[') => re.push('^'),
+                '?' if !in_class => re.push_str("[A-Z]"),
+                '*' if !in_class => re.push_str("[A-Z]*"),
+                _ if ARCHIGRAPHEMES.contains(c) => {
+                    if !in_class && FINAL_ONLY.contains(c) {
+                        final_letter = Some((c, i+1));
+                    }
+                    re.push(c)
+                }
+                _ => return Err(error(format!("unexpected character '{}' at position {}, expected one of {}, ?, *, [ or ]",
+                                              c, i+1, ARCHIGRAPHEMES))),
+            }
+        }
+
+        if in_class {
+            return Err(error("unclosed character class".to_string()));
+        }
+        re.push('$');
+
+        Ok(Pattern { re: Regex::new(&re).unwrap() })
+    }
+
+    /// Check if Latin rasm of a whole word, with or without spaces between blocks, matches.
+    pub fn is_match(&self, rasm: &str) -> bool {
+        if rasm.contains(' ') {
+            self.re.is_match(&rasm.split_whitespace().collect::<String>())
+        } else {
+            self.re.is_match(rasm)
+        }
+    }
+}
+
+/// Find all words of the mushaf whose rasm matches pattern, optionally only inside range,
+/// sorted by their position in the Quran.
+pub fn search(conv: &Converter, quran: &Mushaf, index: &RasmIndex, pattern: &Pattern, range: Option<&QuranRange>) -> Vec<Match> {
+
+    let mut found: Vec<[usize; 3]> = index.words()
+        .filter(|(rasm, _)| pattern.is_match(rasm))
+        .flat_map(|(_, ind)| ind.iter().copied())
+        .filter(|ind| range.is_none_or(|range| range.contains(ind)))
+        .collect();
+    found.sort_unstable();
+
+    found.into_iter().map(|ind @ [sura, vers, word]| {
+        let blocks: Vec<_> = quran.ind[sura-1][vers-1][word-1].iter().map(|&itok| &quran.tok[itok]).collect();
+        Match {
+            index: ind,
+            tok: blocks.iter().map(|(tok, _)| tok.as_str()).collect(),
+            lat: blocks.iter().map(|(_, pal)| conv.strip_paleo(pal)).filter(|b| !b.is_empty()).collect::<Vec<_>>().join(" "),
+            pal: blocks.iter().map(|(_, pal)| pal.as_str()).filter(|b| !b.is_empty()).collect::<Vec<_>>().join(" "),
+        }
+    }).collect()
+}
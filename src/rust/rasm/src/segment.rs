@@ -0,0 +1,175 @@
+/*
+ *    segment.rs
+ *
+ * segmentation of streams of letterblocks into rasm words, for texts written in scriptio continua
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::io::prelude::*;
+use std::io::Result;
+
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Penalty, in natural log, given to a block not found in the vocabulary, besides the probability
+/// of a word seen once.
+const UNKNOWN_PENALTY: f64 = 10.0;
+
+/// Rasm words, as sequences of Latin blocks joined by spaces, and their frequencies.
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    words: HashMap<String, usize>,
+    total: usize,
+    /// blocks of the longest word
+    longest: usize,
+}
+
+/// Way of grouping a stream of blocks into words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segmentation {
+    /// words with their blocks separated by spaces
+    pub words: Vec<String>,
+    /// natural log of the probability of the segmentation
+    pub logprob: f64,
+    /// words not found in the vocabulary
+    pub unknown: usize,
+}
+
+impl Vocabulary {
+
+    pub fn new() -> Vocabulary {
+        Vocabulary::default()
+    }
+
+    /// Count one occurrence of word given by its Latin blocks.
+    pub fn add(&mut self, blocks: &[&str]) {
+        if blocks.is_empty() {
+            return;
+        }
+        *self.words.entry(blocks.join(" ")).or_insert(0) += 1;
+        self.total += 1;
+        self.longest = self.longest.max(blocks.len());
+    }
+
+    /// Read word list or corpus, with whitespace-separated Arabic words.
+    pub fn from_words<R: BufRead>(conv: &Converter, words: R) -> Result<Vocabulary> {
+        let mut voc = Vocabulary::new();
+        for line in words.lines() {
+            let line = line?;
+            let line = conv.normalise(&line);
+            let line = conv.re_ara.replace_all(&line, " ");
+            for word in line.split_whitespace() {
+                let reduced = conv.reduce(word);
+                voc.add(&reduced.lat.split_whitespace().collect::<Vec<_>>());
+            }
+        }
+        Ok(voc)
+    }
+
+    /// Rasm words of the mushaf, optionally only inside range.
+    pub fn from_mushaf(conv: &Converter, quran: &Mushaf, range: Option<&QuranRange>) -> Vocabulary {
+        let mut voc = Vocabulary::new();
+        for ((sura, vers, word), blocks) in quran.words() {
+            if range.is_some_and(|range| !range.contains(&[sura, vers, word])) {
+                continue;
+            }
+            let blocks: Vec<_> = blocks.iter()
+                .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+                .filter(|b| !b.is_empty())
+                .collect();
+            voc.add(&blocks.iter().map(|b| b.as_ref()).collect::<Vec<_>>());
+        }
+        voc
+    }
+
+    fn logprob(&self, word: &str) -> Option<f64> {
+        self.words.get(word).map(|&n| (n as f64 / self.total as f64).ln())
+    }
+
+    /// Find the k most probable segmentations of the blocks into words, best first. Each word is
+    /// scored by its unigram frequency; blocks that cannot be covered by known words are kept as
+    /// one-block unknown words with a heavy penalty. There is no segmentation of no blocks.
+    pub fn segment(&self, blocks: &[&str], k: usize) -> Vec<Segmentation> {
+
+        if blocks.is_empty() {
+            return Vec::new();
+        }
+
+        let unknown = (1.0 / self.total.max(1) as f64).ln() - UNKNOWN_PENALTY;
+
+        // best[i]: k best segmentations of blocks[..i], as (logprob, unknown words, start of last word, rank in best[start])
+        let mut best: Vec<Vec<(f64, usize, usize, usize)>> = vec![Vec::new(); blocks.len()+1];
+        best[0].push((0.0, 0, 0, 0));
+
+        for end in 1..=blocks.len() {
+            let mut cands = Vec::new();
+            for start in end.saturating_sub(self.longest.max(1))..end {
+                let word = blocks[start..end].join(" ");
+                let (lp, unk) = match self.logprob(&word) {
+                    Some(lp) => (lp, 0),
+                    None if end - start == 1 => (unknown, 1),
+                    None => continue,
+                };
+                for (rank, &(prev_lp, prev_unk, _, _)) in best[start].iter().enumerate() {
+                    cands.push((prev_lp + lp, prev_unk + unk, start, rank));
+                }
+            }
+            cands.sort_by(|a, b| b.0.total_cmp(&a.0));
+            cands.truncate(k);
+            best[end] = cands;
+        }
+
+        best[blocks.len()].iter().map(|&(logprob, unknown, _, _)| (logprob, unknown)).enumerate()
+            .map(|(rank, (logprob, unknown))| {
+                // follow back pointers
+                let mut words = Vec::new();
+                let (mut end, mut rank) = (blocks.len(), rank);
+                while end > 0 {
+                    let (_, _, start, prev_rank) = best[end][rank];
+                    words.push(blocks[start..end].join(" "));
+                    end = start;
+                    rank = prev_rank;
+                }
+                words.reverse();
+                Segmentation { words, logprob, unknown }
+            })
+            .collect()
+    }
+}
+
+/// Split text into its stream of Latin letterblocks. Latin input is taken as blocks separated by spaces;
+/// Arabic script, dotted or not, is reduced into archigraphemes first.
+pub fn stream_blocks(conv: &Converter, text: &str) -> Vec<String> {
+    if text.is_ascii() {
+        return text.split_whitespace().map(|b| b.to_string()).collect();
+    }
+    let text = conv.normalise(text);
+    let text = conv.re_ara.replace_all(&text, " ");
+    text.split_whitespace()
+        .flat_map(|tok| conv.reduce(tok).lat.split_whitespace().map(|b| b.to_string()).collect::<Vec<_>>())
+        .collect()
+}
@@ -0,0 +1,188 @@
+/*
+ *    sources.rs
+ *
+ * word by word comparison of two sources of the quranic text
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::BTreeMap;
+
+use crate::align::global_rasm;
+use crate::convert::Converter;
+use crate::mushaf::Mushaf;
+use crate::range::QuranRange;
+
+/// Word of a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceWord {
+    /// original token
+    pub tok: String,
+    /// archigraphemes in Latin script, blocks separated by spaces
+    pub lat: String,
+    /// paleo-orthographic representation, blocks separated by spaces
+    pub pal: String,
+}
+
+/// Kind of difference between the two readings of a word, from the most to the least significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    /// the word is in only one of the sources
+    Missing,
+    /// different archigraphemes
+    Rasm,
+    /// same archigraphemes divided into different letterblocks
+    Blocks,
+    /// same rasm, different dots, vowels or signs
+    Paleo,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Missing => "missing",
+            Kind::Rasm => "rasm",
+            Kind::Blocks => "blocks",
+            Kind::Paleo => "paleo",
+        }
+    }
+}
+
+/// Word that differs between the sources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordDiff {
+    /// (sura, vers, word) in each source, starting in 1; None if the word is missing there
+    pub index_a: Option<[usize; 3]>,
+    pub index_b: Option<[usize; 3]>,
+    pub kind: Kind,
+    pub a: Option<SourceWord>,
+    pub b: Option<SourceWord>,
+}
+
+/// Number of words compared and of differences of each kind in a sura.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuraSummary {
+    pub words: usize,
+    pub missing: usize,
+    pub rasm: usize,
+    pub blocks: usize,
+    pub paleo: usize,
+}
+
+/// Result of comparing two sources.
+#[derive(Debug, Clone, Default)]
+pub struct Comparison {
+    /// differing words in order of index
+    pub diffs: Vec<WordDiff>,
+    /// counts per sura, where words are the pairs of aligned words
+    pub suras: BTreeMap<usize, SuraSummary>,
+}
+
+/// Word of the mushaf at index, if it exists.
+pub fn source_word(conv: &Converter, quran: &Mushaf, [sura, vers, word]: [usize; 3]) -> Option<SourceWord> {
+    let blocks = quran.ind.get(sura-1)?.get(vers-1)?.get(word-1)?;
+    Some(SourceWord {
+        tok: blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect(),
+        lat: blocks.iter()
+            .map(|&itok| conv.strip_paleo(&quran.tok[itok].1))
+            .filter(|b| !b.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        pal: blocks.iter()
+            .map(|&itok| quran.tok[itok].1.as_str())
+            .filter(|b| !b.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    })
+}
+
+/// Words of a verse of the mushaf, empty if it does not exist.
+fn verse_words(conv: &Converter, quran: &Mushaf, sura: usize, vers: usize) -> Vec<SourceWord> {
+    (1..).map_while(|word| source_word(conv, quran, [sura, vers, word])).collect()
+}
+
+/// Walk both sources in parallel by (sura, vers, word), optionally only inside range, and report every word
+/// whose rasm, block segmentation or paleo-orthographic form differs, or that exists in only one of them.
+/// Verses with a different number of words are aligned by rasm, so that a split or merged word does not
+/// shift the rest of the verse.
+pub fn compare(conv: &Converter, a: &Mushaf, b: &Mushaf, range: Option<&QuranRange>) -> Comparison {
+
+    let mut comparison = Comparison::default();
+
+    for sura in 1..=a.ind.len().max(b.ind.len()) {
+        let verses = |q: &Mushaf| q.ind.get(sura-1).map_or(0, |v| v.len());
+        for vers in 1..=verses(a).max(verses(b)) {
+
+            let (words_a, words_b) = (verse_words(conv, a, sura, vers), verse_words(conv, b, sura, vers));
+
+            let pairs: Vec<(Option<usize>, Option<usize>)> = if words_a.len() == words_b.len() {
+                (0..words_a.len()).map(|i| (Some(i), Some(i))).collect()
+            } else {
+                let rasm = |words: &[SourceWord]| -> Vec<String> {
+                    words.iter().map(|w| w.lat.split_whitespace().collect()).collect()
+                };
+                global_rasm(&rasm(&words_a), &rasm(&words_b))
+            };
+
+            for (i, j) in pairs {
+
+                let index_a = i.map(|i| [sura, vers, i+1]);
+                let index_b = j.map(|j| [sura, vers, j+1]);
+                if range.is_some_and(|range| !range.contains(&index_a.or(index_b).unwrap())) {
+                    continue;
+                }
+
+                let (wa, wb) = (i.map(|i| &words_a[i]), j.map(|j| &words_b[j]));
+                let summary = comparison.suras.entry(sura).or_default();
+                summary.words += 1;
+
+                let kind = match (wa, wb) {
+                    (Some(wa), Some(wb)) => {
+                        let (ra, rb): (String, String) = (wa.lat.split_whitespace().collect(), wb.lat.split_whitespace().collect());
+                        if ra != rb {
+                            Kind::Rasm
+                        } else if wa.lat != wb.lat {
+                            Kind::Blocks
+                        } else if wa.pal != wb.pal {
+                            Kind::Paleo
+                        } else {
+                            continue;
+                        }
+                    }
+                    _ => Kind::Missing,
+                };
+
+                match kind {
+                    Kind::Missing => summary.missing += 1,
+                    Kind::Rasm => summary.rasm += 1,
+                    Kind::Blocks => summary.blocks += 1,
+                    Kind::Paleo => summary.paleo += 1,
+                }
+                comparison.diffs.push(WordDiff { index_a, index_b, kind, a: wa.cloned(), b: wb.cloned() });
+            }
+        }
+    }
+
+    comparison
+}
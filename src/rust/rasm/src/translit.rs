@@ -0,0 +1,259 @@
+/*
+ *    translit.rs
+ *
+ * Buckwalter and Safe Buckwalter transliteration of Arabic script, with the extensions needed for the Quran
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Arabic character, its Buckwalter and its Safe Buckwalter transliteration. Safe Buckwalter replaces
+/// the symbols that are special in XML, regexes or the shell with letters.
+const STANDARD: [(char, char, char); 49] = [
+    ('\u{0621}', '\'', 'C'),  // hamza
+    ('\u{0622}', '|', 'M'),   // alif with madda
+    ('\u{0623}', '>', 'O'),   // alif with hamza above
+    ('\u{0624}', '&', 'W'),   // waw with hamza
+    ('\u{0625}', '<', 'I'),   // alif with hamza below
+    ('\u{0626}', '}', 'Q'),   // ya with hamza
+    ('\u{0627}', 'A', 'A'),
+    ('\u{0628}', 'b', 'b'),
+    ('\u{0629}', 'p', 'p'),
+    ('\u{062a}', 't', 't'),
+    ('\u{062b}', 'v', 'v'),
+    ('\u{062c}', 'j', 'j'),
+    ('\u{062d}', 'H', 'H'),
+    ('\u{062e}', 'x', 'x'),
+    ('\u{062f}', 'd', 'd'),
+    ('\u{0630}', '*', 'V'),
+    ('\u{0631}', 'r', 'r'),
+    ('\u{0632}', 'z', 'z'),
+    ('\u{0633}', 's', 's'),
+    ('\u{0634}', '$', 'c'),
+    ('\u{0635}', 'S', 'S'),
+    ('\u{0636}', 'D', 'D'),
+    ('\u{0637}', 'T', 'T'),
+    ('\u{0638}', 'Z', 'Z'),
+    ('\u{0639}', 'E', 'E'),
+    ('\u{063a}', 'g', 'g'),
+    ('\u{0640}', '_', '_'),   // tatweel
+    ('\u{0641}', 'f', 'f'),
+    ('\u{0642}', 'q', 'q'),
+    ('\u{0643}', 'k', 'k'),
+    ('\u{0644}', 'l', 'l'),
+    ('\u{0645}', 'm', 'm'),
+    ('\u{0646}', 'n', 'n'),
+    ('\u{0647}', 'h', 'h'),
+    ('\u{0648}', 'w', 'w'),
+    ('\u{0649}', 'Y', 'Y'),   // alif maqsura
+    ('\u{064a}', 'y', 'y'),
+    ('\u{064b}', 'F', 'F'),   // fathatan
+    ('\u{064c}', 'N', 'N'),   // dammatan
+    ('\u{064d}', 'K', 'K'),   // kasratan
+    ('\u{064e}', 'a', 'a'),
+    ('\u{064f}', 'u', 'u'),
+    ('\u{0650}', 'i', 'i'),
+    ('\u{0651}', '~', '~'),   // shadda
+    ('\u{0652}', 'o', 'o'),   // sukun
+    ('\u{0653}', '^', '^'),   // madda above
+    ('\u{0654}', '#', '#'),   // hamza above
+    ('\u{0670}', '`', 'e'),   // dagger alif
+    ('\u{0671}', '{', 'L'),   // alif wasla
+];
+
+/// Quranic marks, used only by the -quran schemes, as they take up punctuation. They follow the extended
+/// Buckwalter of the Quranic Arabic Corpus; the hamza below, small high madda, pause marks, rub el hizb,
+/// small high yeh and sajda, which it leaves out, use symbols that are free in both schemes. Safe Buckwalter
+/// has no letters left for them, so both columns are the same and Safe Buckwalter with the Quranic marks
+/// is not safe in XML, regexes or the shell.
+const QURANIC: [(char, char, char); 23] = [
+    ('\u{0655}', '(', '('),   // hamza below
+    ('\u{06d6}', 'U', 'U'),   // pause: sala
+    ('\u{06d7}', 'X', 'X'),   // pause: qala
+    ('\u{06d8}', 'B', 'B'),   // pause: small high meem initial
+    ('\u{06d9}', 'R', 'R'),   // pause: lam alif
+    ('\u{06da}', 'J', 'J'),   // pause: small high jeem
+    ('\u{06db}', 'P', 'P'),   // pause: three dots
+    ('\u{06dc}', ':', ':'),   // small high seen
+    ('\u{06de}', 'G', 'G'),   // rub el hizb
+    ('\u{06df}', '@', '@'),   // small high rounded zero
+    ('\u{06e0}', '"', '"'),   // small high upright rectangular zero
+    ('\u{06e2}', '[', '['),   // small high meem
+    ('\u{06e3}', ';', ';'),   // small low seen
+    ('\u{06e4}', ')', ')'),   // small high madda
+    ('\u{06e5}', ',', ','),   // small waw
+    ('\u{06e6}', '.', '.'),   // small ya
+    ('\u{06e7}', '=', '='),   // small high ya
+    ('\u{06e8}', '!', '!'),   // small high noon
+    ('\u{06e9}', '/', '/'),   // sajda
+    ('\u{06ea}', '-', '-'),   // empty centre low stop
+    ('\u{06eb}', '+', '+'),   // empty centre high stop
+    ('\u{06ec}', '%', '%'),   // rounded high stop with filled centre
+    ('\u{06ed}', ']', ']'),   // small low meem
+];
+
+/// ASCII transliteration scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Buckwalter,
+    SafeBuckwalter,
+    /// Buckwalter with the Quranic marks
+    BuckwalterQuran,
+    /// Safe Buckwalter with the Quranic marks, which use punctuation like the Buckwalter ones
+    SafeBuckwalterQuran,
+}
+
+impl Scheme {
+
+    fn safe(&self) -> bool {
+        matches!(self, Scheme::SafeBuckwalter | Scheme::SafeBuckwalterQuran)
+    }
+
+    fn quranic(&self) -> bool {
+        matches!(self, Scheme::BuckwalterQuran | Scheme::SafeBuckwalterQuran)
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "buckwalter" | "bw" => Ok(Scheme::Buckwalter),
+            "safe-buckwalter" | "safebw" | "sbw" => Ok(Scheme::SafeBuckwalter),
+            "buckwalter-quran" | "bwq" => Ok(Scheme::BuckwalterQuran),
+            "safe-buckwalter-quran" | "sbwq" => Ok(Scheme::SafeBuckwalterQuran),
+            _ => Err(format!("unknown transliteration scheme \"{}\", expected buckwalter, safe-buckwalter, \
+                              buckwalter-quran or safe-buckwalter-quran", s)),
+        }
+    }
+}
+
+/// Arabic character that has no transliteration, or ASCII character that would be read back as Arabic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslitError {
+    pub text: String,
+    /// character where the error was found, starting in 1
+    pub position: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for TranslitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot transliterate \"{}\" at position {}: {}", self.text, self.position, self.reason)
+    }
+}
+
+impl Error for TranslitError {}
+
+/// Reversible mapping between Arabic script and one transliteration scheme.
+#[derive(Debug, Clone)]
+pub struct Transliterator {
+    pub scheme: Scheme,
+    to_arabic: HashMap<char, char>,
+    from_arabic: HashMap<char, char>,
+}
+
+impl Transliterator {
+
+    pub fn new(scheme: Scheme) -> Transliterator {
+        let table = if scheme.quranic() { &QURANIC[..] } else { &[] };
+        let pairs: Vec<(char, char)> = STANDARD.iter().chain(table)
+            .map(|&(ara, bw, safe)| (ara, if scheme.safe() { safe } else { bw }))
+            .collect();
+        Transliterator {
+            scheme,
+            to_arabic: pairs.iter().map(|&(ara, asc)| (asc, ara)).collect(),
+            from_arabic: pairs.into_iter().collect(),
+        }
+    }
+
+    /// Write transliterated text in Arabic script. Characters outside the scheme, such as spaces, digits
+    /// or punctuation it does not use, are kept as they are.
+    pub fn to_arabic(&self, text: &str) -> String {
+        text.chars().map(|c| *self.to_arabic.get(&c).unwrap_or(&c)).collect()
+    }
+
+    /// Transliterate Arabic text. Characters of the Arabic blocks without transliteration fail, and so
+    /// do the ASCII characters the scheme uses for Arabic, as they could not be told apart when
+    /// converting back.
+    pub fn from_arabic(&self, text: &str) -> Result<String, TranslitError> {
+        let error = |position: usize, reason: String| TranslitError { text: text.to_string(), position, reason };
+        text.chars().enumerate().map(|(i, c)| {
+            if let Some(&asc) = self.from_arabic.get(&c) {
+                Ok(asc)
+            } else if self.to_arabic.contains_key(&c) {
+                Err(error(i+1, format!("'{}' is already a symbol of the transliteration", c)))
+            } else if ('\u{0600}'..='\u{06ff}').contains(&c) || ('\u{fb50}'..='\u{feff}').contains(&c) {
+                Err(error(i+1, format!("no transliteration for '{}' (U+{:04X})", c, c as u32)))
+            } else {
+                Ok(c)
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::mushaf::{Mushaf, Source};
+
+    #[test]
+    fn table_is_reversible() {
+        let table: Vec<_> = STANDARD.iter().chain(QURANIC.iter()).collect();
+        for column in [|row: &&(char, char, char)| row.1, |row: &&(char, char, char)| row.2] {
+            let symbols: HashSet<char> = table.iter().map(column).collect();
+            assert_eq!(symbols.len(), table.len());
+            assert!(symbols.iter().all(|c| c.is_ascii_graphic()));
+        }
+        assert_eq!(table.iter().map(|row| row.0).collect::<HashSet<_>>().len(), table.len());
+    }
+
+    #[test]
+    fn punctuation_is_kept() {
+        let translit = Transliterator::new(Scheme::Buckwalter);
+        assert_eq!(translit.to_arabic("qAla, 3+4=7. [x] \"y\"; -z!"), "قال\u{064e}, 3+4=7. [خ] \"ي\"; -ز!");
+        let translit = Transliterator::new(Scheme::BuckwalterQuran);
+        assert_eq!(translit.to_arabic("Al^m^"), "ال\u{0653}م\u{0653}");
+    }
+
+    #[test]
+    fn round_trip_uthmani() {
+        let quran = Mushaf::load_source(Source::TanzilUthmani).unwrap();
+        for scheme in [Scheme::BuckwalterQuran, Scheme::SafeBuckwalterQuran] {
+            let translit = Transliterator::new(scheme);
+            for (tok, _) in &quran.tok {
+                let asc = translit.from_arabic(tok).unwrap();
+                assert_eq!(&translit.to_arabic(&asc), tok);
+            }
+        }
+    }
+}
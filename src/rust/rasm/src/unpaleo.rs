@@ -0,0 +1,447 @@
+/*
+ *    unpaleo.rs
+ *
+ * conversion of the paleo-orthographic notation back into Unicode Arabic
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::convert::Converter;
+use crate::paleo::{Base, Letter, Mark, Paleo, Tanwin, Vowel};
+
+/// Letters tried first when several Arabic letters share the same paleo-orthographic form, so that the
+/// standard letter wins over its regional variants, eg. ت over ٺ and ي over ې, and the dotless letters
+/// of the archigraphemes win over the letters that merely lose their dots, eg. ٮ over non-final ں.
+const PREFERRED: &str = "ابتثجحخدذرزسشصضطظعغفقكلمنهويىةٮٯڡںکیھ";
+
+/// Canonical compositions of Unicode involving Arabic letters: base, combining mark, composed letter.
+const COMPOSITIONS: [(char, char, char); 8] = [
+    ('\u{0627}', '\u{0653}', '\u{0622}'),
+    ('\u{0627}', '\u{0654}', '\u{0623}'),
+    ('\u{0648}', '\u{0654}', '\u{0624}'),
+    ('\u{0627}', '\u{0655}', '\u{0625}'),
+    ('\u{064a}', '\u{0654}', '\u{0626}'),
+    ('\u{06d5}', '\u{0654}', '\u{06c0}'),
+    ('\u{06c1}', '\u{0654}', '\u{06c2}'),
+    ('\u{06d2}', '\u{0654}', '\u{06d3}'),
+];
+
+/// Paleo-orthographic string that cannot be converted into Arabic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnpaleoError {
+    pub pal: String,
+    pub reason: String,
+}
+
+impl fmt::Display for UnpaleoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot convert paleo string \"{}\" into Arabic: {}", self.pal, self.reason)
+    }
+}
+
+impl Error for UnpaleoError {}
+
+/// Inverse of Converter::paleo: maps archigraphemes with their dots back to letters and the other signs
+/// back to combining marks.
+#[derive(Debug, Clone)]
+pub struct Unpaleo {
+    /// archigrapheme followed by its dots and shape signs -> Arabic letter
+    letters: HashMap<String, char>,
+}
+
+impl Unpaleo {
+
+    /// Derive the table of letters from the converter, converting every letter of the inventory in
+    /// final and non-final position.
+    pub fn new(conv: &Converter) -> Unpaleo {
+
+        let mut inventory: Vec<char> = conv.chars.letters().chars()
+            // presentation forms are normalised into the letters of the Arabic block
+            .filter(|c| !('\u{fb50}'..='\u{feff}').contains(c))
+            .collect();
+        inventory.sort_by_key(|&c| (PREFERRED.find(c).unwrap_or(PREFERRED.len()), c));
+
+        let mut letters = HashMap::new();
+
+        for letter in inventory {
+            let tok = letter.to_string();
+            // non-final ى keeps its dots unless it carries a dagger alif, as in فَسَوَّىٰهُنَّ, so that context is
+            // tried too for ى to claim the dotless B before ٮ
+            let contexts = [tok.clone(), format!("{}م", tok), format!("{}\u{0670}م", tok)];
+            for pal in contexts.iter().map(|tok| conv.paleo(tok, false).pal) {
+                let key = match pal.parse::<Paleo>().ok().and_then(|p| p.letters().next().map(letter_key)) {
+                    Some(Some(key)) => key,
+                    _ => continue,
+                };
+                letters.entry(key).or_insert(letter);
+            }
+        }
+
+        Unpaleo { letters }
+    }
+
+    /// Convert the paleo-orthographic string of a word into Arabic in NFC. Spaces between letterblocks
+    /// are dropped.
+    pub fn word(&self, pal: &str) -> Result<String, UnpaleoError> {
+
+        let error = |reason: String| UnpaleoError { pal: pal.to_string(), reason };
+        let paleo: Paleo = pal.parse().map_err(|err: crate::paleo::ParsePaleoError| {
+            error(format!("{} at position {}", err.reason, err.position))
+        })?;
+
+        let mut ara = String::new();
+
+        for letter in paleo.letters() {
+            let unknown = || error(format!("letter \"{}\" has no Arabic equivalent", letter));
+            let wasla = letter.has(Mark::Wasla);
+            let base = match letter.base {
+                Base::Hamza => '\u{0621}',
+                Base::Tatweel => '\u{0640}',
+                Base::Arch('A') if wasla => '\u{0671}',
+                Base::Arch(arch) => {
+                    let key = letter_key(letter).unwrap();
+                    // a dotless ya carrying hamza is the ya of ئ
+                    if (arch == 'B' || arch == 'Y') && key.len() == 1 && letter.hamza() == Some(false) {
+                        '\u{064a}'
+                    } else {
+                        *self.letters.get(&key).ok_or_else(unknown)?
+                    }
+                }
+            };
+            if wasla && base != '\u{0671}' {
+                return Err(error(format!("wasla on letter \"{}\"", letter)));
+            }
+            ara.push(base);
+
+            ara.extend(letter.marks.iter().filter_map(|mark| letter_mark(letter.base, mark)));
+        }
+
+        Ok(nfc(&ara))
+    }
+}
+
+/// Archigrapheme of letter with its dots and shape signs, in the order written; None for hamza and tatweel.
+fn letter_key(letter: &Letter) -> Option<String> {
+    let arch = letter.arch()?;
+    let mut key = arch.to_string();
+    for mark in &letter.marks {
+        match mark {
+            Mark::Dots(_) | Mark::Shape(_) => key.push_str(&mark.to_string()),
+            Mark::Apostrophe => key.push('¹'),
+            _ => {},
+        }
+    }
+    Some(key)
+}
+
+/// Combining mark of a sign; None for the signs that are part of the letter itself, like dots.
+fn combining_mark(mark: &Mark) -> Option<char> {
+    match *mark {
+        Mark::Dots(_) | Mark::Apostrophe | Mark::Shape(_) | Mark::Wasla => None,
+        Mark::Vowel(vowel, tanwin) => Some(vowel_mark(vowel, tanwin)),
+        Mark::Shadda => Some('\u{0651}'),
+        Mark::Sukun => Some('\u{0652}'),
+        Mark::Hamza { below } => Some(if below { '\u{0655}' } else { '\u{0654}' }),
+        Mark::Madda => Some('\u{0653}'),
+        Mark::DaggerAlif => Some('\u{0670}'),
+        Mark::Small(c) | Mark::Annotation(c) | Mark::Pause(c) => sign_mark(c),
+    }
+}
+
+/// Combining mark of a sign on a letter with base, where small yeh is written high on a tatweel, as in ٱلنَّبِيِّـۧنَ.
+fn letter_mark(base: Base, mark: &Mark) -> Option<char> {
+    match (base, combining_mark(mark)) {
+        (Base::Tatweel, Some('\u{06e6}')) => Some('\u{06e7}'),
+        (_, c) => c,
+    }
+}
+
+fn vowel_mark(vowel: Vowel, tanwin: Tanwin) -> char {
+    match (vowel, tanwin) {
+        (Vowel::Fatha, Tanwin::None) => '\u{064e}',
+        (Vowel::Damma, Tanwin::None) => '\u{064f}',
+        (Vowel::Kasra, Tanwin::None) => '\u{0650}',
+        (Vowel::Fatha, Tanwin::Closed) => '\u{064b}',
+        (Vowel::Damma, Tanwin::Closed) => '\u{064c}',
+        (Vowel::Kasra, Tanwin::Closed) => '\u{064d}',
+        (Vowel::Fatha, Tanwin::Open) => '\u{08f0}',
+        (Vowel::Damma, Tanwin::Open) => '\u{08f1}',
+        (Vowel::Kasra, Tanwin::Open) => '\u{08f2}',
+    }
+}
+
+/// Combining mark of a small letter, annotation or pausal sign; None if c is not one of them.
+fn sign_mark(c: char) -> Option<char> {
+    let mark = match c {
+        'ᴺ' => '\u{06e8}',
+        'ˢ' => '\u{06dc}',
+        'ₛ' => '\u{06e3}',
+        'ᵐ' => '\u{06e2}',
+        'ₘ' => '\u{06ed}',
+        'ʷ' => '\u{06e5}',
+        'ʸ' => '\u{06e6}',
+        '°' => '\u{06df}',
+        '⁰' => '\u{06e0}',
+        '⌃' => '\u{06eb}',
+        '⌄' => '\u{06ea}',
+        '•' => '\u{06ec}',
+        '⒮' => '\u{06d6}',
+        '⒬' => '\u{06d7}',
+        '⒨' => '\u{06d8}',
+        '⒧' => '\u{06d9}',
+        '⒥' => '\u{06da}',
+        '∴' => '\u{06db}',
+        _ => return None,
+    };
+    Some(mark)
+}
+
+/// Canonical combining class of the Arabic marks produced by the conversion, 0 for the rest.
+fn combining_class(c: char) -> u8 {
+    match c {
+        '\u{064b}' | '\u{08f0}' => 27,
+        '\u{064c}' | '\u{08f1}' => 28,
+        '\u{064d}' | '\u{08f2}' => 29,
+        '\u{064e}' => 30,
+        '\u{064f}' => 31,
+        '\u{0650}' => 32,
+        '\u{0651}' => 33,
+        '\u{0652}' => 34,
+        '\u{0670}' => 35,
+        '\u{0655}' | '\u{06e3}' | '\u{06ea}' | '\u{06ed}' => 220,
+        '\u{0653}' | '\u{0654}' | '\u{06d6}'..='\u{06dc}' | '\u{06df}'..='\u{06e2}' | '\u{06e4}' | '\u{06e7}' | '\u{06e8}'
+            | '\u{06eb}' | '\u{06ec}' => 230,
+        _ => 0,
+    }
+}
+
+/// Normalisation form C of Arabic text: reorder marks by combining class and compose letters with hamza and madda.
+pub fn nfc(text: &str) -> String {
+
+    // canonical ordering, a stable sort of each run of marks
+    let mut chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        while i < chars.len() && combining_class(chars[i]) != 0 {
+            i += 1;
+        }
+        chars[start..i].sort_by_key(|&c| combining_class(c));
+        i = i.max(start+1);
+    }
+
+    // canonical composition, where a mark is blocked by a previous one of the same class
+    let mut out: Vec<char> = Vec::with_capacity(chars.len());
+    let mut starter: Option<usize> = None;
+    let mut last_class = 0;
+    for c in chars {
+        let class = combining_class(c);
+        if let Some(s) = starter {
+            let blocked = out.len() > s+1 && last_class >= class;
+            if !blocked {
+                if let Some(&(_, _, composed)) = COMPOSITIONS.iter().find(|&&(base, mark, _)| base == out[s] && mark == c) {
+                    out[s] = composed;
+                    continue;
+                }
+            }
+        }
+        if class == 0 {
+            starter = Some(out.len());
+            last_class = 0;
+        } else {
+            last_class = class;
+        }
+        out.push(c);
+    }
+
+    out.into_iter().collect()
+}
+
+/// Canonical decomposition of the Arabic letters composed with hamza and madda, eg. أ into ا and hamza above.
+pub fn nfd(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match COMPOSITIONS.iter().find(|&&(_, _, composed)| composed == c) {
+            Some(&(base, mark, _)) => { out.push(base); out.push(mark) }
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::mushaf::{word_blocks, Mushaf, Source};
+
+    /// Paleo string with the marks of each letter in the order they come out of the conversion of text in NFC:
+    /// sorted by combining class up to the next small letter, except for a hamza or madda composed with the
+    /// letter, which goes first.
+    fn canonical(pal: &str) -> String {
+        let mut paleo: Paleo = pal.parse().unwrap();
+        for letter in paleo.blocks.iter_mut().flatten() {
+            let base = letter.base;
+            let class = |mark: &Mark| letter_mark(base, mark).map_or(0, combining_class);
+            for run in letter.marks.split_mut(|mark| matches!(mark, Mark::Small(_)) && class(mark) == 0) {
+                run.sort_by_key(class);
+            }
+            let dotless = letter_key(letter).is_some_and(|key| key.len() == 1);
+            let composed = letter.marks.iter().position(|mark| match (letter.base, mark) {
+                (Base::Arch('A'), Mark::Hamza { .. } | Mark::Madda) => true,
+                (Base::Arch('W'), Mark::Hamza { below: false }) => true,
+                (Base::Arch('B' | 'Y'), Mark::Hamza { below: false }) => dotless,
+                _ => false,
+            });
+            if let Some(i) = composed {
+                let mark = letter.marks.remove(i);
+                let after = letter.marks.iter().take_while(|m| combining_mark(m).is_none()).count();
+                letter.marks.insert(after, mark);
+            }
+        }
+        paleo.to_string()
+    }
+
+    fn round_trip(source: Source) {
+
+        let conv = Converter::new();
+        let unpaleo = Unpaleo::new(&conv);
+        let quran = Mushaf::load_source(source).unwrap();
+
+        let mut failures = vec![];
+
+        for ((sura, vers, word), blocks) in quran.words() {
+            let pal = blocks.iter()
+                .map(|&itok| quran.tok[itok].1.as_str())
+                .filter(|b| !b.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if pal.is_empty() {
+                continue;
+            }
+            let ara = match unpaleo.word(&pal) {
+                Ok(ara) => ara,
+                Err(err) => {
+                    failures.push(format!("{}:{}:{} {}", sura, vers, word, err));
+                    continue;
+                }
+            };
+            let back = word_blocks(&conv, &ara, false).into_iter().map(|(_, pal)| pal).collect::<Vec<_>>().join(" ");
+            if back != canonical(&pal) {
+                failures.push(format!("{}:{}:{} {} -> {} -> {}", sura, vers, word, pal, ara, back));
+            }
+        }
+
+        assert!(failures.is_empty(), "{} words do not round-trip, eg.\n{}", failures.len(), failures[..failures.len().min(20)].join("\n"));
+    }
+
+    #[test]
+    fn round_trip_simple() {
+        round_trip(Source::TanzilSimple);
+    }
+
+    #[test]
+    fn round_trip_uthmani() {
+        round_trip(Source::TanzilUthmani);
+    }
+
+    /// Convert the paleo string of every word back into Arabic and compare it with the NFC of the original
+    /// token. The letters that share a paleo form in the tanzil texts, non-final ى and ٮ before a dagger
+    /// alif or small yeh and small high yeh, are told apart by their context, so no word is ambiguous.
+    fn original_text(source: Source) {
+
+        let conv = Converter::new();
+        let unpaleo = Unpaleo::new(&conv);
+        let quran = Mushaf::load_source(source).unwrap();
+
+        let mut failures = vec![];
+
+        for ((sura, vers, word), blocks) in quran.words() {
+            let tok: String = blocks.iter().map(|&itok| quran.tok[itok].0.as_str()).collect();
+            let pal = blocks.iter()
+                .map(|&itok| quran.tok[itok].1.as_str())
+                .filter(|b| !b.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            if pal.is_empty() {
+                continue;
+            }
+            match unpaleo.word(&pal) {
+                Ok(ara) if ara == nfc(&tok) => {},
+                Ok(ara) => failures.push(format!("{}:{}:{} {} -> {} instead of {}", sura, vers, word, pal, ara, nfc(&tok))),
+                Err(err) => failures.push(format!("{}:{}:{} {}", sura, vers, word, err)),
+            }
+        }
+
+        assert!(failures.is_empty(), "{} words differ from the original, eg.\n{}", failures.len(), failures[..failures.len().min(20)].join("\n"));
+    }
+
+    #[test]
+    fn original_text_simple() {
+        original_text(Source::TanzilSimple);
+    }
+
+    #[test]
+    fn original_text_uthmani() {
+        original_text(Source::TanzilUthmani);
+    }
+
+    #[test]
+    fn unknown_signs() {
+        assert_eq!(letter_mark(Base::Tatweel, &Mark::Small('ʸ')), Some('\u{06e7}'));
+        assert_eq!(letter_mark(Base::Arch('B'), &Mark::Pause('x')), None);
+        assert_eq!(letter_mark(Base::Arch('B'), &Mark::Small('ᵃ')), None);
+    }
+
+    /// Expected forms written out in code points, as given by an independent NFC implementation.
+    #[test]
+    fn known_words() {
+        let unpaleo = Unpaleo::new(&Converter::new());
+        let cases = [
+            ("AɂᵢLᵃᴬHᵘKᵘMᵘ", "\u{0625}\u{0650}\u{0644}\u{064e}\u{0670}\u{0647}\u{064f}\u{0643}\u{064f}\u{0645}\u{064f}"),
+            ("B’ᵚᵃMᵃA˜", "\u{0646}\u{064e}\u{0651}\u{0645}\u{064e}\u{0622}"),
+            ("Aˀᵘ W° Lᵃᴬ˜BˀᵢKᵃ", "\u{0623}\u{064f}\u{0648}\u{06df}\u{0644}\u{064e}\u{0670}\u{0653}\u{0626}\u{0650}\u{0643}\u{064e}"),
+            ("B₂ᵃSᵒB²ᵃHᵒR¹ᵢ Yˀᵘ", "\u{064a}\u{064e}\u{0633}\u{0652}\u{062a}\u{064e}\u{0647}\u{0652}\u{0632}\u{0650}\u{0626}\u{064f}"),
+            ("ـɂᵢ", "\u{0640}\u{0650}\u{0655}"),
+        ];
+        for (pal, expected) in cases {
+            assert_eq!(unpaleo.word(pal).unwrap(), expected, "{}", pal);
+        }
+    }
+
+    #[test]
+    fn nfc_reorders_and_composes() {
+        assert_eq!(nfc("\u{0640}\u{0655}\u{0650}"), "\u{0640}\u{0650}\u{0655}");
+        assert_eq!(nfc("\u{0646}\u{0651}\u{064e}"), "\u{0646}\u{064e}\u{0651}");
+        assert_eq!(nfc("\u{0627}\u{0655}\u{0650}"), "\u{0625}\u{0650}");
+        assert_eq!(nfc("\u{064a}\u{0654}\u{064f}"), "\u{0626}\u{064f}");
+        assert_eq!(nfc("\u{0627}\u{0653}"), "\u{0622}");
+        assert_eq!(nfd("\u{0622}\u{0626}"), "\u{0627}\u{0653}\u{064a}\u{0654}");
+        assert_eq!(nfc(&nfd("\u{0625}\u{0650}\u{0644}\u{064e}\u{0670}\u{0647}")), "\u{0625}\u{0650}\u{0644}\u{064e}\u{0670}\u{0647}");
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ *    verify.rs
+ *
+ * integrity checks of the quran data structures
+ *
+ * MIT License
+ *
+ * Copyright (c) 2022 Alicia González Martínez
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ *
+ ************************************************************************************************************/
+
+use std::fmt;
+
+use crate::convert::Converter;
+use crate::mushaf::{Mushaf, word_blocks};
+use crate::paleo::Paleo;
+use crate::quran::{SURAS, VERSES};
+
+/// Inconsistency found in a mushaf structure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// entry of ind at (sura, vers, word, block) points outside tok
+    IndexOutOfRange { index: (usize, usize, usize, usize), itok: usize, ntok: usize },
+    /// word with no blocks at (sura, vers, word)
+    EmptyWord { index: (usize, usize, usize) },
+    /// entry of tok not referenced by any entry of ind
+    UnusedTok { itok: usize },
+    /// number of suras differs from the expected one
+    SuraCount { found: usize },
+    /// number of verses of sura differs from the expected one
+    VerseCount { sura: usize, expected: usize, found: usize },
+    /// blocks stored for word at (sura, vers, word) differ from the ones produced by the current converter
+    Conversion { index: (usize, usize, usize), stored: Vec<(String, String)>, current: Vec<(String, String)> },
+    /// paleo-orthographic string of tok entry that cannot be parsed or does not print back the same
+    Notation { itok: usize, pal: String, reason: String },
+}
+
+impl Problem {
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Problem::IndexOutOfRange { .. } => "ind",
+            Problem::EmptyWord { .. } => "empty",
+            Problem::UnusedTok { .. } => "unused",
+            Problem::SuraCount { .. } => "suras",
+            Problem::VerseCount { .. } => "verses",
+            Problem::Conversion { .. } => "paleo",
+            Problem::Notation { .. } => "notation",
+        }
+    }
+}
+
+fn fmt_blocks(blocks: &[(String, String)]) -> String {
+    blocks.iter().map(|(tok, pal)| format!("{} {}", tok, pal)).collect::<Vec<_>>().join(" | ")
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\t", self.kind())?;
+        match self {
+            Problem::IndexOutOfRange { index: (s, v, w, b), itok, ntok } =>
+                write!(f, "{}:{}:{}:{} points to {} but tok has {} entries", s, v, w, b, itok, ntok),
+            Problem::EmptyWord { index: (s, v, w) } =>
+                write!(f, "{}:{}:{} has no blocks", s, v, w),
+            Problem::UnusedTok { itok } =>
+                write!(f, "tok entry {} is not referenced in ind", itok),
+            Problem::SuraCount { found } =>
+                write!(f, "{} suras found, expected {}", found, SURAS),
+            Problem::VerseCount { sura, expected, found } =>
+                write!(f, "sura {} has {} verses, expected {}", sura, found, expected),
+            Problem::Conversion { index: (s, v, w), stored, current } =>
+                write!(f, "{}:{}:{}\n- {}\n+ {}", s, v, w, fmt_blocks(stored), fmt_blocks(current)),
+            Problem::Notation { itok, pal, reason } =>
+                write!(f, "tok entry {} \"{}\": {}", itok, pal, reason),
+        }
+    }
+}
+
+/// Check that every entry of ind points inside tok and that every entry of tok is used.
+pub fn check_index(quran: &Mushaf) -> Vec<Problem> {
+
+    let mut problems = Vec::new();
+    let mut used = vec![false; quran.tok.len()];
+
+    for ((s, v, w), blocks) in quran.words() {
+        if blocks.is_empty() {
+            problems.push(Problem::EmptyWord { index: (s, v, w) });
+        }
+        for (b, &itok) in blocks.iter().enumerate() {
+            match used.get_mut(itok) {
+                Some(u) => *u = true,
+                None => problems.push(Problem::IndexOutOfRange { index: (s, v, w, b+1), itok, ntok: quran.tok.len() }),
+            }
+        }
+    }
+
+    problems.extend(used.iter().enumerate().filter(|(_, &u)| !u).map(|(itok, _)| Problem::UnusedTok { itok }));
+
+    problems
+}
+
+/// Check that there are 114 suras with the expected number of verses.
+pub fn check_counts(quran: &Mushaf) -> Vec<Problem> {
+
+    let mut problems = Vec::new();
+
+    if quran.ind.len() != SURAS {
+        problems.push(Problem::SuraCount { found: quran.ind.len() });
+    }
+
+    for (isura, (sura, &expected)) in quran.ind.iter().zip(VERSES.iter()).enumerate() {
+        if sura.len() != expected {
+            problems.push(Problem::VerseCount { sura: isura+1, expected, found: sura.len() });
+        }
+    }
+
+    problems
+}
+
+/// Convert again every stored word and compare the result with the stored blocks.
+pub fn check_conversion(conv: &Converter, quran: &Mushaf, unstable_alif: bool) -> Vec<Problem> {
+
+    let mut problems = Vec::new();
+
+    for (index, blocks) in quran.words() {
+
+        // out of range entries are reported by check_index
+        let stored = match blocks.iter().map(|&i| quran.tok.get(i).cloned()).collect::<Option<Vec<_>>>() {
+            Some(stored) => stored,
+            None => continue,
+        };
+
+        let word = stored.iter().map(|(tok, _)| tok.as_str()).collect::<String>();
+        let current = word_blocks(conv, &word, unstable_alif);
+
+        if current != stored {
+            problems.push(Problem::Conversion { index, stored, current });
+        }
+    }
+
+    problems
+}
+
+/// Check that every stored paleo-orthographic string parses and is printed back unchanged.
+pub fn check_notation(quran: &Mushaf) -> Vec<Problem> {
+    quran.tok.iter().enumerate().filter_map(|(itok, (_, pal))| {
+        let reason = match pal.parse::<Paleo>() {
+            Ok(parsed) if parsed.to_string() == *pal => return None,
+            Ok(parsed) => format!("printed back as \"{}\"", parsed),
+            Err(err) => format!("{} at position {}", err.reason, err.position),
+        };
+        Some(Problem::Notation { itok, pal: pal.clone(), reason })
+    }).collect()
+}
+
+/// Run all checks over the mushaf structure.
+pub fn verify(conv: &Converter, quran: &Mushaf, unstable_alif: bool) -> Vec<Problem> {
+    let mut problems = check_index(quran);
+    problems.extend(check_counts(quran));
+    problems.extend(check_conversion(conv, quran, unstable_alif));
+    problems.extend(check_notation(quran));
+    problems
+}